@@ -0,0 +1,259 @@
+//! Preprocess `data/icons.yaml` and `data/flags.toml` into static lookup tables, so that finding a
+//! built-in icon or flag is a hash lookup instead of parsing both files on every run
+//!
+//! The tables are written to `icons.rs` and `flags.rs` in `OUT_DIR` and included by `src/util.rs`,
+//! which defines the `StaticIcon`, `Variant` and `Color` types they refer to
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use serde_yaml::Value;
+
+fn main() {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is not set"));
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=data/icons.yaml");
+    println!("cargo:rerun-if-changed=data/flags.toml");
+    write(
+        &out_dir.join("icons.rs"),
+        &icon_tables(Path::new("data/icons.yaml")),
+    );
+    write(
+        &out_dir.join("flags.rs"),
+        &flag_table(Path::new("data/flags.toml")),
+    );
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::write(path, contents).unwrap_or_else(|err| panic!("Could not write {path:?}: {err}"));
+}
+
+/// `ICONS` with every icon in file order, `ICON_NAMES` mapping each lowercase name to the first
+/// icon with that name, and `ICON_PREFIXES` with the names ending in `*`, longest first
+fn icon_tables(path: &Path) -> String {
+    let yaml = fs::read_to_string(path).expect("Could not read icons.yaml");
+    let entries: Vec<Value> = serde_yaml::from_str(&yaml).expect("icons.yaml is not a list");
+    let marker = Regex::new(r"\$\{c(\d*)\}").unwrap();
+    let mut icons = String::new();
+    let mut names = phf_codegen::Map::new();
+    let mut seen = BTreeMap::new();
+    let mut prefixes = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let icon = Icon::parse(entry, &marker).unwrap_or_else(|err| {
+            panic!("Invalid icon #{} in icons.yaml: {err}", idx + 1);
+        });
+        for name in &icon.names {
+            if let Some(prefix) = name.strip_suffix('*') {
+                prefixes.push((prefix.to_owned(), idx));
+            } else if !seen.contains_key(name) {
+                seen.insert(name.clone(), idx);
+                names.entry(name.clone(), &idx.to_string());
+            }
+        }
+        writeln!(icons, "    {},", icon.to_rust()).unwrap();
+    }
+    // Stable, so entries earlier in the file win between prefixes of the same length
+    prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+    let prefixes = prefixes
+        .iter()
+        .map(|(prefix, idx)| format!("    ({prefix:?}, {idx}),\n"))
+        .collect::<String>();
+    format!(
+        "pub static ICONS: [StaticIcon; {}] = [\n{icons}];\n\n\
+         pub static ICON_NAMES: phf::Map<&'static str, usize> = {};\n\n\
+         pub static ICON_PREFIXES: [(&str, usize); {}] = [\n{prefixes}];\n",
+        entries.len(),
+        names.build(),
+        prefixes.lines().count(),
+    )
+}
+
+/// An entry of `icons.yaml`, with the art split at its color markers like `AsciiArt::try_from`
+struct Icon {
+    names: Vec<String>,
+    colors: Vec<String>,
+    width: u16,
+    height: u16,
+    art: Vec<(u8, String)>,
+    variant: Option<String>,
+}
+
+impl Icon {
+    fn parse(entry: &Value, marker: &Regex) -> Result<Self, String> {
+        let field = |name| entry.get(name).ok_or(format!("missing {name}"));
+        let names = field("name")?
+            .as_sequence()
+            .ok_or("name is not a list")?
+            .iter()
+            .map(|name| name.as_str().map(str::to_lowercase))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("name is not a list of strings")?;
+        let colors = field("colors")?
+            .as_sequence()
+            .ok_or("colors is not a list")?
+            .iter()
+            .map(color)
+            .collect::<Result<Vec<_>, _>>()?;
+        let width = field("width")?
+            .as_u64()
+            .and_then(|width| u16::try_from(width).ok())
+            .ok_or("width is not a number")?;
+        let art = field("art")?.as_str().ok_or("art is not a string")?;
+        let height = u16::try_from(art.lines().count()).map_err(|err| err.to_string())?;
+        let color_idx = marker
+            .captures_iter(art)
+            .map(|captures| captures[1].parse::<u8>().map_err(|err| err.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(idx) = color_idx
+            .iter()
+            .find(|idx| **idx == 0 || usize::from(**idx) > colors.len())
+        {
+            return Err(format!(
+                "${{c{idx}}} has no color, only {} defined",
+                colors.len()
+            ));
+        }
+        // Text before the first marker is dropped, as at runtime
+        let chunks = marker.split(art).skip(1).map(ToOwned::to_owned);
+        let variant = match entry.get("variant") {
+            Some(variant) => Some(
+                variant
+                    .as_str()
+                    .ok_or("variant is not a string")?
+                    .to_owned(),
+            ),
+            None => None,
+        };
+        Ok(Self {
+            names,
+            colors,
+            width,
+            height,
+            art: color_idx.into_iter().zip(chunks).collect(),
+            variant,
+        })
+    }
+
+    fn to_rust(&self) -> String {
+        let variant = self.variant.as_ref().map_or_else(
+            || "None".to_owned(),
+            |variant| format!("Some(super::Variant::{variant})"),
+        );
+        format!(
+            "StaticIcon {{ names: &{:?}, colors: &[{}], width: {}, height: {}, art: &{:?}, \
+             variant: {variant} }}",
+            self.names,
+            self.colors.join(", "),
+            self.width,
+            self.height,
+            self.art,
+        )
+    }
+}
+
+/// Variants of `Color` without fields, which icons can use by name
+const COLOR_NAMES: [&str; 17] = [
+    "Reset",
+    "Black",
+    "DarkGrey",
+    "Red",
+    "DarkRed",
+    "Green",
+    "DarkGreen",
+    "Yellow",
+    "DarkYellow",
+    "Blue",
+    "DarkBlue",
+    "Magenta",
+    "DarkMagenta",
+    "Cyan",
+    "DarkCyan",
+    "White",
+    "Grey",
+];
+
+/// Rust expression for a color in `icons.yaml`, written like `!AnsiValue 1`, `!Rgb {r, g, b}` or
+/// the name of a `Color` variant
+fn color(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(name) if COLOR_NAMES.contains(&name.as_str()) => Ok(format!("Color::{name}")),
+        Value::Tagged(tagged) if tagged.tag == "AnsiValue" => tagged
+            .value
+            .as_u64()
+            .and_then(|value| u8::try_from(value).ok())
+            .map(|value| format!("Color::AnsiValue({value})"))
+            .ok_or_else(|| format!("invalid ANSI color {:?}", tagged.value)),
+        Value::Tagged(tagged) if tagged.tag == "Rgb" => {
+            let channel = |name| {
+                tagged
+                    .value
+                    .get(name)
+                    .and_then(Value::as_u64)
+                    .and_then(|value| u8::try_from(value).ok())
+                    .ok_or_else(|| format!("invalid RGB color {:?}", tagged.value))
+            };
+            Ok(format!(
+                "Color::Rgb {{ r: {}, g: {}, b: {} }}",
+                channel("r")?,
+                channel("g")?,
+                channel("b")?
+            ))
+        }
+        value => Err(format!("invalid color {value:?}")),
+    }
+}
+
+/// `FLAGS` mapping each scheme name to its colors
+fn flag_table(path: &Path) -> String {
+    let contents = fs::read_to_string(path).expect("Could not read flags.toml");
+    let schemes: BTreeMap<String, Vec<toml::Value>> =
+        toml::from_str(&contents).expect("flags.toml is not a table of color lists");
+    let mut flags = phf_codegen::Map::new();
+    for (name, colors) in &schemes {
+        let colors = colors
+            .iter()
+            .map(|color| {
+                scheme_color(color).unwrap_or_else(|| {
+                    panic!("Invalid color {color:?} in flag {name} in flags.toml")
+                })
+            })
+            .collect::<Vec<_>>();
+        flags.entry(name.as_str(), &format!("&[{}]", colors.join(", ")));
+    }
+    format!(
+        "pub static FLAGS: phf::Map<&'static str, &[Color]> = {};\n",
+        flags.build()
+    )
+}
+
+/// Rust expression for a color in `flags.toml`, either an RGB triple or a hex string
+fn scheme_color(value: &toml::Value) -> Option<String> {
+    let (r, g, b) = match value {
+        toml::Value::Array(channels) => {
+            let channel = |idx: usize| {
+                channels
+                    .get(idx)?
+                    .as_integer()
+                    .and_then(|value| u8::try_from(value).ok())
+            };
+            if channels.len() != 3 {
+                return None;
+            }
+            (channel(0)?, channel(1)?, channel(2)?)
+        }
+        toml::Value::String(hex) => {
+            let hex = hex.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            let [_, r, g, b] = value.to_be_bytes();
+            (r, g, b)
+        }
+        _ => return None,
+    };
+    Some(format!("Color::Rgb {{ r: {r}, g: {g}, b: {b} }}"))
+}
@@ -20,7 +20,6 @@ def get_names(match: re.Match[str]) -> list[str]:
         for x in match.group("name")
         .replace("'", "")
         .replace('"', "")
-        .replace("*", "")
         .split("|")
     ]
 
@@ -0,0 +1,198 @@
+//! Color conversions for adjusting flag colors, using the perceptual Oklab color space
+//! (<https://bottosson.github.io/posts/oklab/>)
+
+use crossterm::style::Color;
+
+use crate::config::ColorMode;
+
+/// A color in the Oklab color space, where `l` is the perceptual lightness from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Oklab {
+    #[must_use]
+    #[allow(clippy::suboptimal_flops)]
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        let [red, green, blue] = [r, g, b].map(|x| to_linear(f32::from(x) / 255.0));
+        // Cone responses
+        let long = (0.412_221_46 * red + 0.536_332_55 * green + 0.051_445_995 * blue).cbrt();
+        let medium = (0.211_903_5 * red + 0.680_699_5 * green + 0.107_396_96 * blue).cbrt();
+        let short = (0.088_302_46 * red + 0.281_718_85 * green + 0.629_978_7 * blue).cbrt();
+        Self {
+            l: 0.210_454_26 * long + 0.793_617_8 * medium - 0.004_072_047 * short,
+            a: 1.977_998_5 * long - 2.428_592_2 * medium + 0.450_593_7 * short,
+            b: 0.025_904_037 * long + 0.782_771_77 * medium - 0.808_675_77 * short,
+        }
+    }
+
+    /// Convert to sRGB, reducing the chroma of colors which are outside of the sRGB gamut
+    #[must_use]
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let mut rgb = self.to_linear_rgb();
+        if !in_gamut(rgb) {
+            // Binary search for the largest chroma that fits, keeping lightness and hue
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..16 {
+                let mid = f32::midpoint(low, high);
+                if in_gamut(self.with_chroma_scale(mid).to_linear_rgb()) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            rgb = self.with_chroma_scale(low).to_linear_rgb();
+        }
+        rgb.map(|x| to_byte(from_linear(x))).into()
+    }
+
+    const fn with_chroma_scale(self, scale: f32) -> Self {
+        Self {
+            l: self.l,
+            a: self.a * scale,
+            b: self.b * scale,
+        }
+    }
+
+    #[allow(clippy::suboptimal_flops)]
+    fn to_linear_rgb(self) -> [f32; 3] {
+        let long = (self.l + 0.396_337_78 * self.a + 0.215_803_76 * self.b).powi(3);
+        let medium = (self.l - 0.105_561_346 * self.a - 0.063_854_17 * self.b).powi(3);
+        let short = (self.l - 0.089_484_18 * self.a - 1.291_485_5 * self.b).powi(3);
+        [
+            4.076_741_7 * long - 3.307_711_6 * medium + 0.230_969_94 * short,
+            -1.268_438 * long + 2.609_757_4 * medium - 0.341_319_38 * short,
+            -0.004_196_086_3 * long - 0.703_418_6 * medium + 1.707_614_7 * short,
+        ]
+    }
+}
+
+/// How [`set_lightness`] treats colors which are already lighter or darker than the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// Always use the target lightness
+    Exact,
+    /// Only lighten colors darker than the target
+    AtLeast,
+    /// Only darken colors lighter than the target
+    AtMost,
+}
+
+/// Set the perceptual lightness of an RGB color, from 0 for black to 1 for white, like hyfetch's
+/// `set_light`. Colors other than [`Color::Rgb`] are returned unchanged
+#[must_use]
+pub fn set_lightness(color: Color, lightness: f32, bound: Bound) -> Color {
+    let Color::Rgb { r, g, b } = color else {
+        return color;
+    };
+    let mut oklab = Oklab::from_rgb(r, g, b);
+    oklab.l = match bound {
+        Bound::Exact => lightness,
+        Bound::AtLeast => oklab.l.max(lightness),
+        Bound::AtMost => oklab.l.min(lightness),
+    };
+    let (r, g, b) = oklab.to_rgb();
+    Color::Rgb { r, g, b }
+}
+
+/// Convert a color to the perceptually closest color available in `mode`
+#[must_use]
+pub fn quantize(color: Color, mode: ColorMode) -> Color {
+    match (mode, color) {
+        (ColorMode::Ansi256, Color::Rgb { r, g, b }) => {
+            let target = Oklab::from_rgb(r, g, b);
+            // The first 16 colors depend on the terminal's theme, so only use the cube and greys
+            (16..=255)
+                .min_by(|a, b| {
+                    distance(target, ansi256_to_oklab(*a))
+                        .total_cmp(&distance(target, ansi256_to_oklab(*b)))
+                })
+                .map_or(color, Color::AnsiValue)
+        }
+        (ColorMode::Ansi16, Color::Rgb { r, g, b }) => nearest_ansi16(Oklab::from_rgb(r, g, b)),
+        (ColorMode::Ansi16, Color::AnsiValue(value)) if value >= 16 => {
+            nearest_ansi16(ansi256_to_oklab(value))
+        }
+        _ => color,
+    }
+}
+
+/// The 16 standard colors, with the default xterm values
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn nearest_ansi16(target: Oklab) -> Color {
+    ANSI16
+        .iter()
+        .map(|(color, (r, g, b))| (*color, distance(target, Oklab::from_rgb(*r, *g, *b))))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(Color::Reset, |(color, _)| color)
+}
+
+/// The color of an xterm 256 color palette entry
+fn ansi256_to_oklab(value: u8) -> Oklab {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match value {
+        0..=15 => {
+            let (r, g, b) = ANSI16[usize::from(value)].1;
+            Oklab::from_rgb(r, g, b)
+        }
+        16..=231 => {
+            let idx = usize::from(value - 16);
+            Oklab::from_rgb(LEVELS[idx / 36], LEVELS[idx / 6 % 6], LEVELS[idx % 6])
+        }
+        232..=255 => {
+            let grey = 8 + (value - 232) * 10;
+            Oklab::from_rgb(grey, grey, grey)
+        }
+    }
+}
+
+#[allow(clippy::suboptimal_flops)]
+fn distance(a: Oklab, b: Oklab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+fn in_gamut(rgb: [f32; 3]) -> bool {
+    rgb.iter().all(|x| (-1e-4..=1.0 + 1e-4).contains(x))
+}
+
+fn to_linear(x: f32) -> f32 {
+    if x <= 0.040_45 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055f32.mul_add(x.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_byte(x: f32) -> u8 {
+    (x * 255.0).round().clamp(0.0, 255.0) as u8
+}
@@ -3,12 +3,21 @@ use std::{ops::Index, sync::Arc};
 use crossterm::style::{Color, StyledContent, Stylize};
 use rayon::prelude::*;
 
-use crate::{config::Orientation, util::AsciiArt};
+use crate::{
+    color::{quantize, set_lightness, Bound},
+    config::{ColorMode, LightDark, Orientation},
+    layout::char_width,
+    util::AsciiArt,
+};
 
 pub trait Colorizer {
     fn colorize(&self, ascii_art: &AsciiArt) -> Vec<StyledContent<String>>;
 }
-pub struct DefaultColors {}
+/// The colors the icon itself defines
+pub struct DefaultColors {
+    /// Colors the terminal supports, which the icon colors are converted to
+    pub color_mode: ColorMode,
+}
 
 impl Colorizer for DefaultColors {
     fn colorize(&self, ascii_art: &AsciiArt) -> Vec<StyledContent<String>> {
@@ -17,11 +26,12 @@ impl Colorizer for DefaultColors {
             .art
             .par_iter()
             .map(|(idx, text)| -> StyledContent<String> {
-                text.clone().with(
+                text.clone().with(quantize(
                     *colors
                         .get((*idx as usize) - 1)
                         .expect("Invalid color index"),
-                )
+                    self.color_mode,
+                ))
             })
             .collect::<Vec<StyledContent<String>>>()
     }
@@ -30,11 +40,40 @@ impl Colorizer for DefaultColors {
 pub struct FlagColors {
     pub color_scheme: Arc<[Color]>,
     pub orientation: Orientation,
+    /// Background the colors are adjusted for, or `None` to use the scheme colors as they are
+    pub light_dark: Option<LightDark>,
+    /// Lightness from 0 to 1 to set the colors to. Without it, colors are only lightened on dark
+    /// backgrounds and only darkened on light backgrounds when `light_dark` is set
+    pub lightness: Option<f32>,
+    /// Colors the terminal supports, which the scheme colors are converted to
+    pub color_mode: ColorMode,
 }
 
 impl FlagColors {
+    /// The scheme colors, with their lightness adjusted for the terminal background and converted
+    /// to colors the terminal supports
+    #[must_use]
+    pub fn colors(&self) -> Arc<[Color]> {
+        let (lightness, bound) = match (self.light_dark, self.lightness) {
+            (None, None) => {
+                return self
+                    .color_scheme
+                    .iter()
+                    .map(|color| quantize(*color, self.color_mode))
+                    .collect()
+            }
+            (_, Some(lightness)) => (lightness, Bound::Exact),
+            (Some(LightDark::Light), None) => (LightDark::Light.default_lightness(), Bound::AtMost),
+            (Some(light_dark), None) => (light_dark.default_lightness(), Bound::AtLeast),
+        };
+        self.color_scheme
+            .iter()
+            .map(|color| quantize(set_lightness(*color, lightness, bound), self.color_mode))
+            .collect()
+    }
     fn length_to_colors(&self, length: usize) -> impl Index<usize, Output = Color> {
-        let preset_len = self.color_scheme.len(); //6
+        let color_scheme = self.colors();
+        let preset_len = color_scheme.len(); //6
         let center = preset_len / 2; // 4
 
         let repeats = length / preset_len; // 1
@@ -51,17 +90,17 @@ impl FlagColors {
             weights[preset_len - border - 1] += 1;
             border += 1;
         }
-        self.weights_to_colors(weights.into_par_iter())
+        Self::weights_to_colors(&color_scheme, weights.into_par_iter())
     }
     fn weights_to_colors(
-        &self,
+        color_scheme: &[Color],
         weights: impl IndexedParallelIterator<Item = usize>,
     ) -> impl Index<usize, Output = Color> {
         weights
             .enumerate()
             .flat_map(|(idx, weight)| {
                 //Create iterator with length `weight` containing a color
-                rayon::iter::repeatn(self.color_scheme[idx], weight)
+                rayon::iter::repeatn(color_scheme[idx], weight)
             })
             .collect::<Vec<Color>>()
     }
@@ -85,12 +124,19 @@ impl Colorizer for FlagColors {
 
             Orientation::Vertical => {
                 //Requires txt has at least one line and is rectangular
-                let colors = self.length_to_colors(ascii_art.width as usize);
+                let width = ascii_art.width as usize;
+                let colors = self.length_to_colors(width);
 
                 txt.par_lines()
-                    .flat_map(|line| {
-                        line.par_char_indices()
-                            .map(|(idx, ch)| ch.to_string().with(colors[idx]))
+                    .flat_map_iter(|line| {
+                        // Stripes are indexed by column, as characters can be several bytes long
+                        // and some take up two columns
+                        line.chars()
+                            .scan(0, |column, ch| {
+                                let idx = (*column).min(width.saturating_sub(1));
+                                *column += char_width(ch);
+                                Some(ch.to_string().with(colors[idx]))
+                            })
                             .chain([String::from("\n").with(Color::Reset)])
                     })
                     .collect()
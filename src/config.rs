@@ -1,14 +1,105 @@
-use clap::{Parser, ValueEnum};
+use std::{path::PathBuf, time::Duration};
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Parser, Eq, PartialEq)]
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crossterm::style::Color;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::{
+    color::Oklab,
+    custom::CustomModule,
+    info::Field,
+    terminal,
+    util::{format_color, parse_color, SchemeColor},
+};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Parser, PartialEq)]
 #[command(author, version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
     #[arg(short, long)]
     pub scheme_name: Option<String>,
     #[arg(value_enum, short, long)]
     pub orientation: Option<Orientation>,
     #[arg(short, long)]
     pub icon_name: Option<String>,
+    /// Whether the terminal background is light or dark, to keep flag colors readable
+    #[arg(value_enum, long)]
+    pub light_dark: Option<LightDark>,
+    /// Lightness of the flag colors, from 0.0 for black to 1.0 for white
+    #[arg(long)]
+    pub lightness: Option<f32>,
+    /// Colors the terminal supports, detected from COLORTERM and TERM by default
+    #[arg(value_enum, long)]
+    pub color_mode: Option<ColorMode>,
+    /// Print plain text without colors, which is also done when `NO_COLOR` is set or the output is
+    /// not a terminal
+    #[arg(long)]
+    #[serde(default)]
+    pub no_color: bool,
+    /// Icon file to show instead of a named icon
+    #[arg(long)]
+    pub icon_path: Option<PathBuf>,
+    #[arg(value_enum, short, long)]
+    pub format: Option<Format>,
+    /// Modules to show, in display order
+    #[arg(value_enum, short, long, value_delimiter = ',')]
+    pub modules: Option<Vec<Field>>,
+    #[arg(value_enum, short, long)]
+    pub render: Option<Render>,
+    /// Where to place the logo relative to the system information
+    #[arg(value_enum, long)]
+    pub layout: Option<Layout>,
+    /// Whether to show the small version of the logo, by default only when the full logo doesn't
+    /// fit into the terminal
+    #[arg(value_enum, long)]
+    pub logo_size: Option<LogoSize>,
+    /// Milliseconds to wait for each module before reporting it as unavailable
+    #[arg(long)]
+    pub timeout: Option<u64>,
+    /// Print how long each module took
+    #[arg(long)]
+    #[serde(default)]
+    pub timings: bool,
+    /// List the names of all flag color schemes
+    #[arg(long)]
+    #[serde(skip)]
+    pub list_schemes: bool,
+    /// List the names of all icons
+    #[arg(long)]
+    #[serde(skip)]
+    pub list_icons: bool,
+    /// Show each scheme as a stripe bar and each icon in its colors when listing them
+    #[arg(long)]
+    #[serde(skip)]
+    pub preview: bool,
+    /// Choose a flag scheme interactively and save it to the config file
+    #[arg(long)]
+    #[serde(skip)]
+    pub setup: bool,
+    /// Label templates overriding the default label of each module
+    #[arg(skip)]
+    #[serde(default)]
+    pub labels: FxHashMap<Field, String>,
+    /// Value templates overriding the default value of each module
+    #[arg(skip)]
+    #[serde(default)]
+    pub formats: FxHashMap<Field, String>,
+    #[arg(skip)]
+    #[serde(default)]
+    pub theme: Theme,
+    /// User defined flag color schemes
+    #[arg(skip)]
+    #[serde(default)]
+    pub schemes: FxHashMap<String, Vec<SchemeColor>>,
+    /// User defined modules, shown after the built-in modules
+    #[arg(skip)]
+    #[serde(default)]
+    pub custom: Vec<CustomModule>,
 }
 
 impl Config {
@@ -16,27 +107,126 @@ impl Config {
     #[must_use]
     pub fn with_icon(self, icon_name: impl Into<String>) -> Self {
         Self {
-            scheme_name: self.scheme_name,
             icon_name: Some(Into::<String>::into(icon_name)),
-            orientation: self.orientation,
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_icon_path(self, icon_path: impl Into<PathBuf>) -> Self {
+        Self {
+            icon_path: Some(icon_path.into()),
+            ..self
         }
     }
     #[must_use]
     pub fn with_scheme_name(self, scheme_name: impl Into<String>) -> Self {
         Self {
             scheme_name: Some(Into::<String>::into(scheme_name)),
-            icon_name: self.icon_name,
-            orientation: self.orientation,
+            ..self
         }
     }
     #[must_use]
     pub fn with_orientation(self, orientation: &Orientation) -> Self {
         Self {
-            scheme_name: self.scheme_name,
-            icon_name: self.icon_name,
             orientation: Some(*orientation),
+            ..self
         }
     }
+    #[must_use]
+    pub fn with_light_dark(self, light_dark: LightDark) -> Self {
+        Self {
+            light_dark: Some(light_dark),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_lightness(self, lightness: f32) -> Self {
+        Self {
+            lightness: Some(lightness),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_color_mode(self, color_mode: ColorMode) -> Self {
+        Self {
+            color_mode: Some(color_mode),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_no_color(self, no_color: bool) -> Self {
+        Self { no_color, ..self }
+    }
+    #[must_use]
+    pub fn with_format(self, format: Format) -> Self {
+        Self {
+            format: Some(format),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_modules(self, modules: impl Into<Vec<Field>>) -> Self {
+        Self {
+            modules: Some(modules.into()),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_layout(self, layout: Layout) -> Self {
+        Self {
+            layout: Some(layout),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_logo_size(self, logo_size: LogoSize) -> Self {
+        Self {
+            logo_size: Some(logo_size),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_render(self, render: Render) -> Self {
+        Self {
+            render: Some(render),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_timeout(self, timeout: u64) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_timings(self, timings: bool) -> Self {
+        Self { timings, ..self }
+    }
+    #[must_use]
+    pub fn with_label(mut self, field: Field, label: impl Into<String>) -> Self {
+        self.labels.insert(field, label.into());
+        self
+    }
+    #[must_use]
+    pub fn with_module_format(mut self, field: Field, format: impl Into<String>) -> Self {
+        self.formats.insert(field, format.into());
+        self
+    }
+    #[must_use]
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+    #[must_use]
+    pub fn with_scheme(mut self, name: impl Into<String>, colors: Vec<SchemeColor>) -> Self {
+        self.schemes.insert(name.into(), colors);
+        self
+    }
+    #[must_use]
+    pub fn with_custom_module(mut self, module: CustomModule) -> Self {
+        self.custom.push(module);
+        self
+    }
     /// Create new struct containing user settings
     #[must_use]
     pub fn new(
@@ -48,20 +238,254 @@ impl Config {
             scheme_name: scheme_name.map(|x| x.to_string()),
             orientation,
             icon_name: icon_name.map(|x| x.to_string()),
+            ..Self::default()
         }
     }
     #[must_use]
-    pub fn with_config(self, other: Self) -> Self {
+    pub fn with_config(mut self, other: Self) -> Self {
+        self.labels.extend(other.labels);
+        self.formats.extend(other.formats);
+        self.schemes.extend(other.schemes);
         Self {
+            command: other.command.or(self.command),
             scheme_name: other.scheme_name.or(self.scheme_name),
             orientation: other.orientation.or(self.orientation),
             icon_name: other.icon_name.or(self.icon_name),
+            light_dark: other.light_dark.or(self.light_dark),
+            lightness: other.lightness.or(self.lightness),
+            color_mode: other.color_mode.or(self.color_mode),
+            no_color: other.no_color || self.no_color,
+            icon_path: other.icon_path.or(self.icon_path),
+            format: other.format.or(self.format),
+            modules: other.modules.or(self.modules),
+            render: other.render.or(self.render),
+            layout: other.layout.or(self.layout),
+            logo_size: other.logo_size.or(self.logo_size),
+            timeout: other.timeout.or(self.timeout),
+            timings: other.timings || self.timings,
+            list_schemes: other.list_schemes || self.list_schemes,
+            list_icons: other.list_icons || self.list_icons,
+            preview: other.preview || self.preview,
+            setup: other.setup || self.setup,
+            labels: self.labels,
+            formats: self.formats,
+            theme: self.theme.with_theme(other.theme),
+            schemes: self.schemes,
+            custom: if other.custom.is_empty() {
+                self.custom
+            } else {
+                other.custom
+            },
         }
     }
 }
 
+/// Commands run instead of showing the system information
+#[derive(Debug, Clone, Subcommand, PartialEq, Eq)]
+pub enum Command {
+    /// Check an icon file for undefined colors, wrong widths, duplicate names and trailing
+    /// whitespace
+    LintIcons {
+        /// Icon file in the same format as `data/icons.yaml`
+        file: PathBuf,
+    },
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq)]
 pub enum Orientation {
     Horizontal,
     Vertical,
 }
+
+/// Background of the terminal, which flag colors are made lighter or darker for like in hyfetch
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq)]
+pub enum LightDark {
+    Light,
+    Dark,
+    /// Ask the terminal for its background color
+    Auto,
+}
+
+impl LightDark {
+    /// Replace `Auto` with the background reported by the terminal, assuming a dark background if
+    /// the terminal doesn't report one
+    #[must_use]
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => terminal::background_color(Duration::from_millis(100)).map_or(
+                Self::Dark,
+                |(r, g, b)| {
+                    if Oklab::from_rgb(r, g, b).l > 0.5 {
+                        Self::Light
+                    } else {
+                        Self::Dark
+                    }
+                },
+            ),
+            other => other,
+        }
+    }
+
+    /// Lightness flag colors are clamped to, at most on light backgrounds and at least on dark
+    /// ones
+    #[must_use]
+    pub const fn default_lightness(self) -> f32 {
+        match self {
+            Self::Light => 0.4,
+            Self::Dark | Self::Auto => 0.65,
+        }
+    }
+}
+
+/// Colors a terminal can show, flag and theme colors are converted to the closest color available
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+pub enum ColorMode {
+    /// 24-bit RGB colors
+    #[default]
+    Truecolor,
+    /// The xterm 256 color palette
+    Ansi256,
+    /// The 16 standard colors, which depend on the terminal's theme
+    Ansi16,
+}
+
+/// Output format for the collected system information
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+pub enum Format {
+    /// Logo and system information for display in a terminal
+    #[default]
+    Text,
+    /// System information only, as a single JSON document
+    Json,
+}
+
+/// Order in which system information is printed
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+pub enum Render {
+    /// Print modules in the order of the module list, independent of how long each one takes
+    #[default]
+    Ordered,
+    /// Print modules as soon as they are available, for the lowest latency
+    Streaming,
+}
+
+/// Where the logo is placed relative to the system information
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+pub enum Layout {
+    /// Logo to the left of the system information
+    #[default]
+    LogoLeft,
+    /// Logo to the right of the system information
+    LogoRight,
+    /// Logo above the system information
+    LogoTop,
+    /// System information without a logo
+    NoLogo,
+}
+
+/// Size of the logo, for operating systems with a small version of their logo
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoSize {
+    /// The small logo when the full logo doesn't fit into the terminal
+    #[default]
+    Auto,
+    Small,
+    Full,
+}
+
+/// Colors and separator used to display the system information
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct Theme {
+    #[serde(default, with = "color_option")]
+    pub label_color: Option<Color>,
+    pub separator: Option<String>,
+    #[serde(default, with = "color_option")]
+    pub value_color: Option<Color>,
+    #[serde(default, with = "color_option")]
+    pub title_color: Option<Color>,
+}
+
+impl Theme {
+    /// Merge two themes, preferring the settings in `other`
+    #[must_use]
+    pub fn with_theme(self, other: Self) -> Self {
+        Self {
+            label_color: other.label_color.or(self.label_color),
+            separator: other.separator.or(self.separator),
+            value_color: other.value_color.or(self.value_color),
+            title_color: other.title_color.or(self.title_color),
+        }
+    }
+
+    /// Color of the labels, defaulting to the second color of the logo like neofetch does
+    #[must_use]
+    pub fn label_color(&self, logo_colors: &[Color]) -> Color {
+        self.label_color
+            .or_else(|| logo_colors.get(1).or_else(|| logo_colors.first()).copied())
+            .unwrap_or(Color::Reset)
+    }
+
+    /// Color of the `user@host` title, defaulting to the first color of the logo
+    #[must_use]
+    pub fn title_color(&self, logo_colors: &[Color]) -> Color {
+        self.title_color
+            .or_else(|| logo_colors.first().copied())
+            .unwrap_or(Color::Reset)
+    }
+
+    #[must_use]
+    pub fn value_color(&self) -> Color {
+        self.value_color.unwrap_or(Color::Reset)
+    }
+
+    #[must_use]
+    pub fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(": ")
+    }
+}
+
+/// (De)serialize an optional color as an ANSI color index, a hex string or a color name
+mod color_option {
+    use super::{format_color, parse_color, Color, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ColorValue {
+        Index(u8),
+        Name(String),
+    }
+
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(Color::AnsiValue(value)) => serializer.serialize_u8(*value),
+            Some(color) => serializer.serialize_str(&format_color(*color)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        match ColorValue::deserialize(deserializer)? {
+            ColorValue::Index(value) => Ok(Some(Color::AnsiValue(value))),
+            ColorValue::Name(name) => parse_color(&name)
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fs,
+    io::Read,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use arcstr::ArcStr;
+
+/// User defined module showing the first line of a command's output or of a file
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq)]
+pub struct CustomModule {
+    pub label: String,
+    /// Shell command to run
+    pub command: Option<String>,
+    /// File to read, if no command is set
+    pub file: Option<PathBuf>,
+}
+
+impl CustomModule {
+    /// First non-empty line of the command's output or of the file, `None` if it could not be
+    /// read or if the command is still running at `deadline`
+    #[must_use]
+    pub fn run(&self, deadline: Option<Instant>) -> Option<ArcStr> {
+        let output = match (&self.command, &self.file) {
+            (Some(command), _) => run_command(command, deadline)?,
+            (None, Some(file)) => fs::read_to_string(file).ok()?,
+            (None, None) => return None,
+        };
+        output
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(ArcStr::from)
+    }
+}
+
+/// How often to check whether a command has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// Time allowed for killing commands which are still running at the deadline, after which their
+/// results are no longer waited for
+pub const KILL_TIME: Duration = Duration::from_millis(50);
+
+/// Time allowed for reading the rest of the output once a command has exited, as programs it left
+/// running in the background can keep its stdout open
+const OUTPUT_TIME: Duration = Duration::from_millis(50);
+
+/// Output of `command`, `None` if it fails or if it is still running at `deadline`, in which case
+/// it is killed
+fn run_command(command: &str, deadline: Option<Instant>) -> Option<String> {
+    let mut child = shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // Read the output while waiting so that the command cannot block on a full pipe
+    let mut stdout = child.stdout.take()?;
+    let (chunk_tx, chunks) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0; 4096];
+        while let Ok(len @ 1..) = stdout.read(&mut buf) {
+            if chunk_tx.send(buf[..len].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        let remaining = deadline.map_or(POLL_INTERVAL, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        });
+        if remaining.is_zero() {
+            kill(&mut child);
+            child.wait().ok();
+            return None;
+        }
+        thread::sleep(remaining.min(POLL_INTERVAL));
+    };
+    if !status.success() {
+        return None;
+    }
+    let until = Instant::now() + OUTPUT_TIME;
+    let until = deadline.map_or(until, |deadline| until.min(deadline));
+    let mut output = Vec::new();
+    // Ends when stdout is closed, or when the time is up
+    while let Ok(chunk) = chunks.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        output.extend(chunk);
+    }
+    Some(String::from_utf8_lossy(&output).into_owned())
+}
+
+#[cfg(target_family = "windows")]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(target_family = "windows"))]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    // Start a new process group, so that the programs the command runs can be killed with it
+    shell.process_group(0);
+    shell
+}
+
+#[cfg(target_family = "windows")]
+fn kill(child: &mut Child) {
+    child.kill().ok();
+}
+
+/// Kill the process group of `child`, which takes the same `&mut Child` as on Windows
+#[cfg(not(target_family = "windows"))]
+#[allow(clippy::needless_pass_by_ref_mut)]
+fn kill(child: &mut Child) {
+    if let Ok(pid) = i32::try_from(child.id()) {
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+}
+
+/// Result of running a single custom module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomProbe {
+    /// Position of the module in the list passed to [`get_async`]
+    pub idx: usize,
+    /// `None` if the module could not be read
+    pub value: Option<ArcStr>,
+    /// Time taken by the module, `None` if it was still running at the deadline
+    pub elapsed: Option<Duration>,
+}
+
+impl CustomProbe {
+    #[must_use]
+    pub const fn timed_out(&self) -> bool {
+        self.elapsed.is_none()
+    }
+}
+
+/// Run all custom modules in parallel, sending a [`CustomProbe`] for each module as soon as it has
+/// finished. Commands still running at `deadline` are killed
+pub fn get_async(tx: &Sender<CustomProbe>, modules: &[CustomModule], deadline: Option<Instant>) {
+    // Commands spend most of their time waiting, so give each one its own thread rather than
+    // holding up the rayon threads the built-in modules run on
+    thread::scope(|s| {
+        for (idx, module) in modules.iter().enumerate() {
+            s.spawn(move || {
+                let start = Instant::now();
+                let value = module.run(deadline);
+                let timed_out =
+                    value.is_none() && deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                tx.send(CustomProbe {
+                    idx,
+                    value,
+                    elapsed: (!timed_out).then(|| start.elapsed()),
+                })
+                .ok();
+            });
+        }
+    });
+}
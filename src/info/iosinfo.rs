@@ -1,6 +1,6 @@
 use arcstr::ArcStr;
 
-use crate::info::OSInfo;
+use crate::info::{Battery, Disk, Memory, OSInfo, Uptime};
 
 pub struct IosInfo {}
 impl Default for IosInfo {
@@ -36,16 +36,16 @@ impl OSInfo for IosInfo {
         Vec::new()
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<Disk> {
         Vec::new()
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -100,7 +100,7 @@ impl OSInfo for IosInfo {
         todo!()
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
+    fn uptime(&self) -> Option<Uptime> {
         todo!()
     }
 
@@ -1,13 +1,10 @@
 #![cfg(target_os = "linux")]
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
+use crate::info::{Battery, ChargeState, Disk, Memory, OSInfo, Uptime};
 use anyhow::anyhow;
 use arcstr::ArcStr;
 use glob::glob;
 use itertools::Itertools;
-use libc::{
-    getifaddrs, statvfs, timespec, AF_INET, AF_INET6, IFA_F_DEPRECATED, IFF_LOOPBACK, IFF_RUNNING,
-};
+use libc::{getifaddrs, statvfs, timespec, AF_INET, IFA_F_DEPRECATED, IFF_LOOPBACK, IFF_RUNNING};
 use pci_ids::Device;
 use platform_info::UNameAPI;
 use platform_info::{PlatformInfo, PlatformInfoAPI};
@@ -22,7 +19,7 @@ use std::{
     ffi::{CStr, CString},
     fs,
     mem::{self, MaybeUninit},
-    net::{Ipv4Addr, Ipv6Addr},
+    net::Ipv4Addr,
     sync::OnceLock,
 };
 
@@ -48,13 +45,14 @@ impl LinuxInfo {
     fn os_release(&self) -> &FxHashMap<ArcStr, ArcStr> {
         self.os_release.get_or_init(|| {
             let mut res = FxHashMap::default();
-            let data = fs::read_to_string("/etc/os-release").ok().unwrap();
-            res.par_extend(data.par_lines().map(|line| {
-                let (x, y) = line.split_once('=').unwrap();
-                (
+            let data = fs::read_to_string("/etc/os-release").unwrap_or_default();
+            // Skip blank lines and comments
+            res.par_extend(data.par_lines().filter_map(|line| {
+                let (x, y) = line.split_once('=')?;
+                Some((
                     x.to_owned().into_boxed_str().into(),
                     y.trim_matches('"').to_owned().into_boxed_str().into(),
-                )
+                ))
             }));
             res
         })
@@ -286,7 +284,7 @@ impl OSInfo for LinuxInfo {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         let re = regex::Regex::new(r"Mem(Total|Available):\W*(\d*)").unwrap();
         let mem = fs::read_to_string("/proc/meminfo").ok()?;
         let caps: (u64, u64) = re
@@ -294,16 +292,14 @@ impl OSInfo for LinuxInfo {
             .map(|x| str::parse::<u64>(x.get(2).unwrap().as_str()).unwrap())
             .collect_tuple()?;
 
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format((caps.0 - caps.1) << 10, 2),
-            bytecount_format(caps.0 << 10, 2),
-        ))
+        Some(Memory {
+            used: (caps.0 - caps.1) << 10,
+            total: caps.0 << 10,
+        })
     }
 
     fn ip(&self) -> Vec<ArcStr> {
         let mut ipv4_addrs = FxHashSet::<Ipv4Addr>::default();
-        let mut ipv6_addrs = FxHashSet::<Ipv6Addr>::default();
         unsafe {
             let mut addrs = mem::MaybeUninit::<*mut libc::ifaddrs>::uninit();
             getifaddrs(addrs.as_mut_ptr());
@@ -332,15 +328,6 @@ impl OSInfo for LinuxInfo {
                         .swap_bytes();
                     ipv4_addrs.insert(Ipv4Addr::from(ipv4));
                 }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET6 {
-                    #[allow(clippy::cast_ptr_alignment)]
-                    let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
-                        .sin6_addr
-                        .s6_addr;
-                    if !ipv6.starts_with(&[0xfe, 0x80]) {
-                        ipv6_addrs.insert(Ipv6Addr::from(ipv6));
-                    }
-                }
                 // if addr.ifa_next.is_null() {
                 //     break;
                 // }
@@ -348,22 +335,17 @@ impl OSInfo for LinuxInfo {
             }
         };
 
-        vec![
-            ArcStr::from(
-                ipv4_addrs
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect_vec()
-                    .join(", "),
-            ),
-            /*ipv6_addrs.iter().fold(ArcStr::new(), |x, y| {
-                (if x.is_empty() { x } else { x + ", " }) + &y.to_string()
-            }),*/
-        ]
+        vec![ArcStr::from(
+            ipv4_addrs
+                .iter()
+                .map(std::string::ToString::to_string)
+                .collect_vec()
+                .join(", "),
+        )]
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
-        (|| -> Option<Vec<(ArcStr, ArcStr)>> {
+    fn disks(&self) -> Vec<Disk> {
+        (|| -> Option<Vec<Disk>> {
             let mnt = fs::read_to_string("/proc/mounts").ok()?;
             let re = regex::Regex::new(r"(^/dev/(loop|ram|fd))|(/var/snap)").unwrap();
             Some(
@@ -379,9 +361,9 @@ impl OSInfo for LinuxInfo {
                         if !line.starts_with("/dev/") {
                             return None;
                         }
-                        return Some(line.split_ascii_whitespace());
+                        Some(line.split_ascii_whitespace())
                     })
-                    .filter_map(|mut x| -> Option<(ArcStr, ArcStr)> {
+                    .filter_map(|mut x| -> Option<Disk> {
                         let (Some(_name), Some(mount), Some(_filesystemm)) =
                             (x.next(), x.next(), x.next())
                         else {
@@ -399,48 +381,39 @@ impl OSInfo for LinuxInfo {
                                 return None;
                             }
                             // println!("Size Used: {size_used}, Block Size {block_size}");
-                            size_used.checked_mul(block_size).map(|bytes| {
-                                (
-                                    arcstr::format!("Disk ({mount})"),
-                                    arcstr::format!(
-                                        "{}/ {}",
-                                        bytecount_format(bytes, 0),
-                                        bytecount_format(total * block_size, 0)
-                                    ),
-                                )
+                            Some(Disk {
+                                mount: ArcStr::from(mount),
+                                used: size_used.checked_mul(block_size)?,
+                                total: total.checked_mul(block_size)?,
                             })
                         }
                     })
-                    .collect::<Vec<(ArcStr, ArcStr)>>(),
+                    .collect::<Vec<Disk>>(),
             )
         })()
         .unwrap_or_default()
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        //TODO refactor this into a vector
-        Some(ArcStr::from(
-            glob::glob("/sys/class/power_supply/BAT*/")
-                .ok()?
-                .filter_map(|x| {
-                    x.map_err(|op| anyhow!(op))
-                        .and_then(|path| {
-                            let mut bat = fs::read_to_string(path.join("capacity"))?
-                                .trim()
-                                .to_string()
-                                + "% ";
-                            if let Ok(status) = fs::read_to_string(path.join("status")) {
-                                if !status.contains("Unknown") {
-                                    bat += &status;
-                                }
-                            }
-                            Ok(bat.trim().to_string())
-                        })
-                        .ok()
-                })
-                .collect_vec()
-                .join(", "),
-        ))
+    fn battery(&self) -> Vec<Battery> {
+        glob::glob("/sys/class/power_supply/BAT*/")
+            .map(|paths| {
+                paths
+                    .filter_map(|x| {
+                        x.map_err(|op| anyhow!(op))
+                            .and_then(|path| {
+                                let capacity =
+                                    fs::read_to_string(path.join("capacity"))?.trim().parse()?;
+                                let status = fs::read_to_string(path.join("status"))
+                                    .map_or(ChargeState::Unknown, |status| {
+                                        ChargeState::from(status.trim())
+                                    });
+                                Ok(Battery { capacity, status })
+                            })
+                            .ok()
+                    })
+                    .collect_vec()
+            })
+            .unwrap_or_default()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -452,17 +425,13 @@ impl OSInfo for LinuxInfo {
             .map(ArcStr::from)
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
+    fn uptime(&self) -> Option<Uptime> {
         unsafe {
             let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
             libc::clock_gettime(libc::CLOCK_BOOTTIME, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec.into())
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
-            ))
+            Some(Uptime {
+                seconds: u64::try_from(time.as_ref()?.tv_sec).ok()?,
+            })
         }
     }
 
@@ -471,7 +440,19 @@ impl OSInfo for LinuxInfo {
         None
     }
     fn id(&self) -> ArcStr {
-        self.os_release();
-        self.os_release().get("ID").unwrap().clone()
+        // os-release defaults to `linux` when there is no ID
+        self.os_release()
+            .get("ID")
+            .cloned()
+            .unwrap_or(arcstr::literal!("linux"))
+    }
+    fn os_name(&self) -> Option<ArcStr> {
+        self.os_release().get("NAME").cloned()
+    }
+    fn id_like(&self) -> Vec<ArcStr> {
+        self.os_release()
+            .get("ID_LIKE")
+            .map(|id_like| id_like.split_whitespace().map(ArcStr::from).collect())
+            .unwrap_or_default()
     }
 }
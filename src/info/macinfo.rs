@@ -1,5 +1,5 @@
 #![cfg(target_os = "macos")]
-use super::OSInfo;
+use super::{Battery, Disk, Memory, OSInfo, Uptime};
 use arcstr::ArcStr;
 use libc::timespec;
 use platform_info::{PlatformInfo, PlatformInfoAPI, UNameAPI};
@@ -79,16 +79,16 @@ impl OSInfo for MacInfo {
         Vec::new()
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<Disk> {
         Vec::new()
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -151,17 +151,13 @@ impl OSInfo for MacInfo {
         arcstr::literal!("mac")
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
+    fn uptime(&self) -> Option<Uptime> {
         unsafe {
             let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
             libc::clock_gettime(libc::CLOCK_UPTIME_RAW, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec)
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
-            ))
+            Some(Uptime {
+                seconds: u64::try_from(time.as_ref()?.tv_sec).ok()?,
+            })
         }
     }
 
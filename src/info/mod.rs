@@ -1,10 +1,18 @@
 use std::{
-    str::FromStr,
-    sync::{mpsc::Sender, Arc},
+    fmt::Display,
+    iter,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
 };
 
 use arcstr::ArcStr;
-use crossterm::style::{Color, Stylize};
+use clap::ValueEnum;
+use crossterm::style::{Color, StyledContent, Stylize};
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+
+use crate::util::{bytecount_format, fill_template};
 
 #[cfg(target_os = "ios")]
 use crate::info::iosinfo::IosInfo as get_info;
@@ -36,14 +44,14 @@ pub trait OSInfo: Send + Sync {
     fn gpus(&self) -> Vec<ArcStr> {
         Vec::new()
     }
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<Disk> {
         Vec::new()
     }
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
     fn locale(&self) -> Option<ArcStr> {
         None
@@ -58,7 +66,16 @@ pub trait OSInfo: Send + Sync {
         None
     }
     fn id(&self) -> ArcStr;
-    fn uptime(&self) -> Option<ArcStr>;
+    /// IDs of the operating systems this one is based on, closest first, eg `ID_LIKE` in
+    /// os-release
+    fn id_like(&self) -> Vec<ArcStr> {
+        Vec::new()
+    }
+    /// Name of the operating system without its version, eg `NAME` in os-release
+    fn os_name(&self) -> Option<ArcStr> {
+        None
+    }
+    fn uptime(&self) -> Option<Uptime>;
     fn ip(&self) -> Vec<ArcStr>;
     fn displays(&self) -> Vec<ArcStr> {
         Vec::new()
@@ -94,180 +111,698 @@ pub trait OSInfo: Send + Sync {
     }
 }
 
-// #[derive(Debug)]
-// pub struct Info {
-//     pub os: Option<ArcStr>,
-//     pub machine: Option<ArcStr>,
-//     pub kernel: Option<ArcStr>,
-//     pub uptime: Option<ArcStr>,
-//     pub username: Option<ArcStr>,
-//     pub hostname: Option<ArcStr>,
-//     pub resolution: Vec<ArcStr>,
-//     pub wm: Option<ArcStr>,
-//     pub de: Option<ArcStr>,
-//     pub shell: Option<ArcStr>,
-//     pub cpu: Option<ArcStr>,
-//     pub font: Option<ArcStr>,
-//     pub cursor: Option<ArcStr>,
-//     pub terminal: Option<ArcStr>,
-//     pub terminal_font: Option<ArcStr>,
-//     pub gpus: Vec<ArcStr>,
-//     pub memory: Option<ArcStr>,
-//     pub disks: Vec<(ArcStr, ArcStr)>,
-//     pub battery: Option<ArcStr>,
-//     pub locale: Option<ArcStr>,
-//     pub theme: Option<ArcStr>,
-//     pub icons: Option<ArcStr>,
-//     pub ip: Vec<ArcStr>,
-//     pub id: ArcStr,
-// }
+/// Key identifying a single piece of system information
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Field {
+    Os,
+    Machine,
+    Kernel,
+    Uptime,
+    Username,
+    Hostname,
+    Displays,
+    Wm,
+    De,
+    Shell,
+    Cpu,
+    SysFont,
+    Cursor,
+    Terminal,
+    TermFont,
+    Gpus,
+    Memory,
+    Disks,
+    Battery,
+    Locale,
+    Theme,
+    Icons,
+    Ip,
+}
+
+impl Field {
+    pub const ALL: [Self; 23] = [
+        Self::Os,
+        Self::Machine,
+        Self::Kernel,
+        Self::Uptime,
+        Self::Username,
+        Self::Hostname,
+        Self::Displays,
+        Self::Wm,
+        Self::De,
+        Self::Shell,
+        Self::Cpu,
+        Self::SysFont,
+        Self::Cursor,
+        Self::Terminal,
+        Self::TermFont,
+        Self::Gpus,
+        Self::Memory,
+        Self::Disks,
+        Self::Battery,
+        Self::Locale,
+        Self::Theme,
+        Self::Icons,
+        Self::Ip,
+    ];
+
+    /// Human readable name of this field
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Os => "OS",
+            Self::Machine => "Machine",
+            Self::Kernel => "Kernel",
+            Self::Uptime => "Uptime",
+            Self::Username => "Username",
+            Self::Hostname => "Hostname",
+            Self::Displays => "Display",
+            Self::Wm => "WM",
+            Self::De => "DE",
+            Self::Shell => "Shell",
+            Self::Cpu => "CPU",
+            Self::SysFont => "System Font",
+            Self::Cursor => "Cursor",
+            Self::Terminal => "Terminal",
+            Self::TermFont => "Terminal Font",
+            Self::Gpus => "GPU",
+            Self::Memory => "Memory",
+            Self::Disks => "Disk",
+            Self::Battery => "Battery",
+            Self::Locale => "Locale",
+            Self::Theme => "Theme",
+            Self::Icons => "Icons",
+            Self::Ip => "IP",
+        }
+    }
+
+    /// Default template for the label shown next to the value when displaying this field
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Displays => "Display {index}",
+            Self::Gpus => "GPU {index}",
+            Self::Disks => "Disk ({mount})",
+            field => field.title(),
+        }
+    }
+
+    /// Run the getter for this field
+    fn probe(self, getter: &impl OSInfo) -> Option<Entry> {
+        match self {
+            Self::Os => getter.os().map(Entry::Os),
+            Self::Machine => getter.machine().map(Entry::Machine),
+            Self::Kernel => getter.kernel().map(Entry::Kernel),
+            Self::Uptime => getter.uptime().map(Entry::Uptime),
+            Self::Username => getter.username().map(Entry::Username),
+            Self::Hostname => getter.hostname().map(Entry::Hostname),
+            Self::Displays => Some(Entry::Displays(getter.displays())),
+            Self::Wm => getter.wm().map(Entry::Wm),
+            Self::De => getter.de().map(Entry::De),
+            Self::Shell => getter.shell().map(Entry::Shell),
+            Self::Cpu => getter.cpu().map(Entry::Cpu),
+            Self::SysFont => getter.sys_font().map(Entry::SysFont),
+            Self::Cursor => getter.cursor().map(Entry::Cursor),
+            Self::Terminal => getter.terminal().map(Entry::Terminal),
+            Self::TermFont => getter.term_font().map(Entry::TermFont),
+            Self::Gpus => Some(Entry::Gpus(getter.gpus())),
+            Self::Memory => getter.memory().map(Entry::Memory),
+            Self::Disks => Some(Entry::Disks(getter.disks())),
+            Self::Battery => Some(Entry::Battery(getter.battery())),
+            Self::Locale => getter.locale().map(Entry::Locale),
+            Self::Theme => getter.theme().map(Entry::Theme),
+            Self::Icons => getter.icons().map(Entry::Icons),
+            Self::Ip => Some(Entry::Ip(getter.ip())),
+        }
+    }
+}
+
+/// Value gathered for a single [`Field`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Os(ArcStr),
+    Machine(ArcStr),
+    Kernel(ArcStr),
+    Uptime(Uptime),
+    Username(ArcStr),
+    Hostname(ArcStr),
+    Displays(Vec<ArcStr>),
+    Wm(ArcStr),
+    De(ArcStr),
+    Shell(ArcStr),
+    Cpu(ArcStr),
+    SysFont(ArcStr),
+    Cursor(ArcStr),
+    Terminal(ArcStr),
+    TermFont(ArcStr),
+    Gpus(Vec<ArcStr>),
+    Memory(Memory),
+    Disks(Vec<Disk>),
+    Battery(Vec<Battery>),
+    Locale(ArcStr),
+    Theme(ArcStr),
+    Icons(ArcStr),
+    Ip(Vec<ArcStr>),
+}
+
+impl Entry {
+    #[must_use]
+    pub const fn field(&self) -> Field {
+        match self {
+            Self::Os(_) => Field::Os,
+            Self::Machine(_) => Field::Machine,
+            Self::Kernel(_) => Field::Kernel,
+            Self::Uptime(_) => Field::Uptime,
+            Self::Username(_) => Field::Username,
+            Self::Hostname(_) => Field::Hostname,
+            Self::Displays(_) => Field::Displays,
+            Self::Wm(_) => Field::Wm,
+            Self::De(_) => Field::De,
+            Self::Shell(_) => Field::Shell,
+            Self::Cpu(_) => Field::Cpu,
+            Self::SysFont(_) => Field::SysFont,
+            Self::Cursor(_) => Field::Cursor,
+            Self::Terminal(_) => Field::Terminal,
+            Self::TermFont(_) => Field::TermFont,
+            Self::Gpus(_) => Field::Gpus,
+            Self::Memory(_) => Field::Memory,
+            Self::Disks(_) => Field::Disks,
+            Self::Battery(_) => Field::Battery,
+            Self::Locale(_) => Field::Locale,
+            Self::Theme(_) => Field::Theme,
+            Self::Icons(_) => Field::Icons,
+            Self::Ip(_) => Field::Ip,
+        }
+    }
+
+    /// Values which can be referenced from label and value templates, one set for each line
+    /// displayed for this entry. Every set contains at least `value`.
+    #[must_use]
+    pub fn placeholders(&self) -> Vec<Vec<(&'static str, String)>> {
+        match self {
+            Self::Displays(values) | Self::Gpus(values) => values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    vec![
+                        ("value", value.to_string()),
+                        ("index", (idx + 1).to_string()),
+                    ]
+                })
+                .collect(),
+            Self::Ip(values) => values
+                .iter()
+                .map(|value| vec![("value", value.to_string())])
+                .collect(),
+            Self::Disks(disks) => disks
+                .iter()
+                .map(|disk| {
+                    vec![
+                        ("value", disk.to_string()),
+                        ("mount", disk.mount.to_string()),
+                        ("used", bytecount_format(disk.used, 0)),
+                        ("total", bytecount_format(disk.total, 0)),
+                        ("percent", format!("{:.0}", disk.percent())),
+                        ("used_bytes", disk.used.to_string()),
+                        ("total_bytes", disk.total.to_string()),
+                    ]
+                })
+                .collect(),
+            Self::Battery(batteries) => batteries
+                .iter()
+                .map(|battery| {
+                    vec![
+                        ("value", battery.to_string()),
+                        ("capacity", battery.capacity.to_string()),
+                        ("status", battery.status.to_string()),
+                    ]
+                })
+                .collect(),
+            Self::Uptime(uptime) => vec![vec![
+                ("value", uptime.to_string()),
+                ("days", (uptime.seconds / 86400).to_string()),
+                ("hours", (uptime.seconds / 3600 % 24).to_string()),
+                ("minutes", (uptime.seconds / 60 % 60).to_string()),
+                ("seconds", (uptime.seconds % 60).to_string()),
+            ]],
+            Self::Memory(memory) => vec![vec![
+                ("value", memory.to_string()),
+                ("used", bytecount_format(memory.used, 2)),
+                ("total", bytecount_format(memory.total, 2)),
+                ("percent", format!("{:.0}", memory.percent())),
+                ("used_bytes", memory.used.to_string()),
+                ("total_bytes", memory.total.to_string()),
+            ]],
+            Self::Os(value)
+            | Self::Machine(value)
+            | Self::Kernel(value)
+            | Self::Username(value)
+            | Self::Hostname(value)
+            | Self::Wm(value)
+            | Self::De(value)
+            | Self::Shell(value)
+            | Self::Cpu(value)
+            | Self::SysFont(value)
+            | Self::Cursor(value)
+            | Self::Terminal(value)
+            | Self::TermFont(value)
+            | Self::Locale(value)
+            | Self::Theme(value)
+            | Self::Icons(value) => vec![vec![("value", value.to_string())]],
+        }
+    }
+
+    /// Label and value pairs to display for this entry, using the default label and value
+    /// templates where `label` or `format` are `None`
+    #[must_use]
+    pub fn lines(&self, label: Option<&str>, format: Option<&str>) -> Vec<(ArcStr, ArcStr)> {
+        let label = label.unwrap_or_else(|| self.field().label());
+        let format = format.unwrap_or("{value}");
+        self.placeholders()
+            .iter()
+            .map(|values| {
+                (
+                    ArcStr::from(fill_template(label, values)),
+                    ArcStr::from(fill_template(format, values)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Snapshot of all system information gathered by [`get_info`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SystemInfo {
+    pub os: Option<ArcStr>,
+    pub machine: Option<ArcStr>,
+    pub kernel: Option<ArcStr>,
+    pub uptime: Option<Uptime>,
+    pub username: Option<ArcStr>,
+    pub hostname: Option<ArcStr>,
+    pub displays: Vec<ArcStr>,
+    pub wm: Option<ArcStr>,
+    pub de: Option<ArcStr>,
+    pub shell: Option<ArcStr>,
+    pub cpu: Option<ArcStr>,
+    pub sys_font: Option<ArcStr>,
+    pub cursor: Option<ArcStr>,
+    pub terminal: Option<ArcStr>,
+    pub term_font: Option<ArcStr>,
+    pub gpus: Vec<ArcStr>,
+    pub memory: Option<Memory>,
+    pub disks: Vec<Disk>,
+    pub battery: Vec<Battery>,
+    pub locale: Option<ArcStr>,
+    pub theme: Option<ArcStr>,
+    pub icons: Option<ArcStr>,
+    pub ip: Vec<ArcStr>,
+    /// Values of the user defined modules which could be read, see [`crate::custom`]
+    pub custom: Vec<CustomValue>,
+    pub id: ArcStr,
+}
+
+impl SystemInfo {
+    /// Store an entry in the matching field of the snapshot
+    pub fn insert(&mut self, entry: Entry) {
+        match entry {
+            Entry::Os(value) => self.os = Some(value),
+            Entry::Machine(value) => self.machine = Some(value),
+            Entry::Kernel(value) => self.kernel = Some(value),
+            Entry::Uptime(value) => self.uptime = Some(value),
+            Entry::Username(value) => self.username = Some(value),
+            Entry::Hostname(value) => self.hostname = Some(value),
+            Entry::Displays(values) => self.displays = values,
+            Entry::Wm(value) => self.wm = Some(value),
+            Entry::De(value) => self.de = Some(value),
+            Entry::Shell(value) => self.shell = Some(value),
+            Entry::Cpu(value) => self.cpu = Some(value),
+            Entry::SysFont(value) => self.sys_font = Some(value),
+            Entry::Cursor(value) => self.cursor = Some(value),
+            Entry::Terminal(value) => self.terminal = Some(value),
+            Entry::TermFont(value) => self.term_font = Some(value),
+            Entry::Gpus(values) => self.gpus = values,
+            Entry::Memory(value) => self.memory = Some(value),
+            Entry::Disks(values) => self.disks = values,
+            Entry::Battery(values) => self.battery = values,
+            Entry::Locale(value) => self.locale = Some(value),
+            Entry::Theme(value) => self.theme = Some(value),
+            Entry::Icons(value) => self.icons = Some(value),
+            Entry::Ip(values) => self.ip = values,
+        }
+    }
+}
+
+impl Extend<Entry> for SystemInfo {
+    fn extend<T: IntoIterator<Item = Entry>>(&mut self, iter: T) {
+        for entry in iter {
+            self.insert(entry);
+        }
+    }
+}
+
+/// Value of a user defined module
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CustomValue {
+    pub label: ArcStr,
+    pub value: ArcStr,
+}
+
+/// Physical memory usage, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Memory {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl Memory {
+    #[must_use]
+    pub fn percent(&self) -> f64 {
+        percent(self.used, self.total)
+    }
+}
+
+impl Display for Memory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 2),
+            bytecount_format(self.total, 2)
+        )
+    }
+}
+
+/// Usage of a mounted filesystem, in bytes
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Disk {
+    pub mount: ArcStr,
+    pub used: u64,
+    pub total: u64,
+}
+
+impl Disk {
+    #[must_use]
+    pub fn percent(&self) -> f64 {
+        percent(self.used, self.total)
+    }
+}
+
+impl Display for Disk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 0),
+            bytecount_format(self.total, 0)
+        )
+    }
+}
+
+/// Time since boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Uptime {
+    pub seconds: u64,
+}
+
+impl Display for Uptime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds = i64::try_from(self.seconds).unwrap_or(i64::MAX);
+        let uptime = time::Duration::seconds(seconds).to_string();
+        if cfg!(target_family = "windows") {
+            // Windows shows hours and minutes only
+            let uptime = uptime.split_inclusive('m').next().unwrap_or_default();
+            write!(
+                f,
+                "{}",
+                uptime.replace('h', " hours, ").replace('m', " mins")
+            )
+        } else {
+            write!(f, "{uptime}")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChargeState {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl From<&str> for ChargeState {
+    fn from(status: &str) -> Self {
+        match status {
+            "Charging" => Self::Charging,
+            "Discharging" => Self::Discharging,
+            "Full" => Self::Full,
+            "Not charging" => Self::NotCharging,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl Display for ChargeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Charging => "Charging",
+            Self::Discharging => "Discharging",
+            Self::Full => "Full",
+            Self::NotCharging => "Not charging",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
+
+/// Charge level of a single battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Battery {
+    /// Capacity in percent
+    pub capacity: u8,
+    pub status: ChargeState,
+}
+
+impl Display for Battery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            ChargeState::Unknown => write!(f, "{}%", self.capacity),
+            status => write!(f, "{}% {status}", self.capacity),
+        }
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    used as f64 / total as f64 * 100.0
+}
 
 #[must_use]
 pub fn get_id() -> ArcStr {
     get_info::new().id()
 }
-#[allow(clippy::too_many_lines)]
-pub fn get_async(tx: &Sender<(ArcStr, ArcStr)>) {
-    let getter = Arc::new(get_info::new());
+
+/// IDs of the operating systems this one is based on, see [`OSInfo::id_like`]
+#[must_use]
+pub fn get_id_like() -> Vec<ArcStr> {
+    get_info::new().id_like()
+}
+
+/// Names to look up the icon for this system by, see [`icon_ids`]
+#[must_use]
+pub fn get_icon_ids() -> Vec<ArcStr> {
+    let getter = get_info::new();
+    icon_ids(getter.id(), getter.id_like(), getter.os_name())
+}
+
+/// Names to look up the icon for a system by, from the most to the least specific: its ID, the
+/// IDs of the systems it is based on, its name, and finally the generic `linux` icon on Linux
+#[must_use]
+pub fn icon_ids(id: ArcStr, id_like: Vec<ArcStr>, name: Option<ArcStr>) -> Vec<ArcStr> {
+    iter::once(id)
+        .chain(id_like)
+        .chain(name)
+        .chain(cfg!(target_os = "linux").then_some(arcstr::literal!("linux")))
+        .collect()
+}
+
+/// Title line shown above the system information, eg `user@host`
+#[must_use]
+pub fn get_title() -> ArcStr {
+    let getter = get_info::new();
     let username = getter.username().unwrap_or_default();
     let hostname = getter.hostname().unwrap_or_default();
-    let y = arcstr::format!("{username}@{hostname}");
-    let repeats =
-        ArcStr::from_str(std::str::from_utf8(vec![b'-'; y.len()].as_slice()).unwrap()).unwrap();
-    tx.send((y, ArcStr::new())).unwrap();
-    tx.send((repeats, ArcStr::new())).unwrap();
-    rayon::scope(|s| {
-        // general_readout: general_readout.clone(),
-        s.spawn(|_| {
-            getter
-                .battery()
-                .and_then(|e| tx.send((arcstr::literal!("Battery"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .cpu()
-                .and_then(|e| tx.send((arcstr::literal!("cpu"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .cursor()
-                .and_then(|e| tx.send((arcstr::literal!("cursor"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .de()
-                .and_then(|e| tx.send((arcstr::literal!("de"), e)).ok());
-        });
-        s.spawn(|_| {
-            for e in getter.disks() {
-                tx.send(e).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .sys_font()
-                .and_then(|e| tx.send((arcstr::literal!("sys_font"), e)).ok());
-        });
-        s.spawn(|_| {
-            for (idx, e) in getter.gpus().into_iter().enumerate() {
-                tx.send((arcstr::format!("GPU {}", idx + 1), e)).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .hostname()
-                .and_then(|e| tx.send((arcstr::literal!("hostname"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .icons()
-                .and_then(|e| tx.send((arcstr::literal!("icons"), e)).ok());
-        });
-        s.spawn(|_| {
-            for e in getter.ip() {
-                tx.send((arcstr::literal!("IP"), e)).ok();
+    arcstr::format!("{username}@{hostname}")
+}
+
+/// Gather the system information for `modules` in parallel into a single snapshot, leaving out
+/// modules that take longer than `timeout`
+#[must_use]
+pub fn get_info(modules: &[Field], timeout: Option<Duration>) -> SystemInfo {
+    let (tx, rx) = mpsc::channel();
+    let probe_modules = modules.to_vec();
+    thread::spawn(move || get_async(&tx, &probe_modules));
+    let mut info = SystemInfo {
+        id: get_id(),
+        ..SystemInfo::default()
+    };
+    info.extend(Collector::new(rx, modules, timeout).filter_map(|probe| probe.entry));
+    info
+}
+
+/// Gather the system information for `modules` in parallel, sending a [`Probe`] for each module as
+/// soon as it has finished
+pub fn get_async(tx: &Sender<Probe>, modules: &[Field]) {
+    let getter = &get_info::new();
+    // Give each module its own thread rather than a rayon thread, so that a getter that hangs,
+    // eg on a stale network mount, cannot keep the modules after it from starting
+    thread::scope(|s| {
+        for field in modules.iter().copied().unique() {
+            s.spawn(move || {
+                let start = Instant::now();
+                let entry = field.probe(getter);
+                tx.send(Probe {
+                    field,
+                    entry,
+                    elapsed: Some(start.elapsed()),
+                })
+                .ok();
+            });
+        }
+    });
+}
+
+/// Result of running the getter for a single module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub field: Field,
+    /// `None` if the information is not available on this system
+    pub entry: Option<Entry>,
+    /// Time taken by the getter, `None` if it did not finish before the timeout
+    pub elapsed: Option<Duration>,
+}
+
+impl Probe {
+    #[must_use]
+    pub const fn timed_out(&self) -> bool {
+        self.elapsed.is_none()
+    }
+
+    /// Label and value pairs to display for this probe, see [`Entry::lines`]
+    #[must_use]
+    pub fn lines(&self, label: Option<&str>, format: Option<&str>) -> Vec<(ArcStr, ArcStr)> {
+        match &self.entry {
+            Some(entry) => entry.lines(label, format),
+            None if self.timed_out() => vec![(
+                label.map_or_else(
+                    || ArcStr::from(self.field.title()),
+                    |label| ArcStr::from(fill_template(label, &[]).trim()),
+                ),
+                arcstr::literal!("unavailable"),
+            )],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Iterator over the probes sent by [`get_async`], yielding exactly one probe per module.
+///
+/// Modules which have not reported once the timeout has passed are yielded as timed out, so a
+/// hung getter cannot block the caller.
+pub struct Collector {
+    rx: Receiver<Probe>,
+    pending: Vec<Field>,
+    deadline: Option<Instant>,
+}
+
+impl Collector {
+    #[must_use]
+    pub fn new(rx: Receiver<Probe>, modules: &[Field], timeout: Option<Duration>) -> Self {
+        Self {
+            rx,
+            pending: modules.iter().copied().unique().collect(),
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+}
+
+impl Iterator for Collector {
+    type Item = Probe;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.pending.is_empty() {
+            let received = match self.deadline {
+                Some(deadline) => self
+                    .rx
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok(),
+                None => self.rx.recv().ok(),
+            };
+            let Some(probe) = received else {
+                return Some(Probe {
+                    field: self.pending.remove(0),
+                    entry: None,
+                    elapsed: None,
+                });
+            };
+            if let Some(idx) = self.pending.iter().position(|x| *x == probe.field) {
+                self.pending.remove(idx);
+                return Some(probe);
             }
-        });
-        s.spawn(|_| {
-            getter
-                .kernel()
-                .and_then(|e| tx.send((arcstr::literal!("kernel"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .locale()
-                .and_then(|e| tx.send((arcstr::literal!("locale"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .machine()
-                .and_then(|e| tx.send((arcstr::literal!("machine"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .memory()
-                .and_then(|e| tx.send((arcstr::literal!("memory"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .os()
-                .and_then(|e| tx.send((arcstr::literal!("OS"), e)).ok());
-        });
-        s.spawn(|_| {
-            for (idx, e) in getter.displays().into_iter().enumerate() {
-                tx.send((arcstr::format!("Display {}", idx + 1), e)).ok();
+        }
+        None
+    }
+}
+
+/// Iterator adapter yielding probes in the order of a module list, regardless of the order in
+/// which they finish
+pub struct InOrder<I> {
+    probes: I,
+    modules: Vec<Field>,
+    next: usize,
+    slots: FxHashMap<Field, Probe>,
+}
+
+impl<I: Iterator<Item = Probe>> InOrder<I> {
+    pub fn new(probes: I, modules: &[Field]) -> Self {
+        Self {
+            probes,
+            modules: modules.iter().copied().unique().collect(),
+            next: 0,
+            slots: FxHashMap::default(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Probe>> Iterator for InOrder<I> {
+    type Item = Probe;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let field = *self.modules.get(self.next)?;
+            if let Some(probe) = self.slots.remove(&field) {
+                self.next += 1;
+                return Some(probe);
             }
-        });
-        s.spawn(|_| {
-            getter
-                .shell()
-                .and_then(|e| tx.send((arcstr::literal!("Shell"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .terminal()
-                .and_then(|e| tx.send((arcstr::literal!("Terminal"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .term_font()
-                .and_then(|e| tx.send((arcstr::literal!("Term_font"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .theme()
-                .and_then(|e| tx.send((arcstr::literal!("Theme"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .uptime()
-                .and_then(|e| tx.send((arcstr::literal!("Uptime"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .username()
-                .and_then(|e| tx.send((arcstr::literal!("Username"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .wm()
-                .and_then(|e| tx.send((arcstr::literal!("WM"), e)).ok());
-        });
-    });
-    let (dark, light) = palette();
-    tx.send((ArcStr::new(), dark)).ok();
-    tx.send((ArcStr::new(), light)).ok();
-}
-
-fn palette() -> (ArcStr, ArcStr) {
-    (
-        (0..8u8)
-            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
-            .collect::<String>()
-            .into(),
-        (8..16u8)
-            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
-            .collect::<String>()
-            .into(),
-    )
+            let probe = self.probes.next()?;
+            self.slots.insert(probe.field, probe);
+        }
+    }
+}
+
+/// Blocks in the 8 standard and 8 bright colors of the terminal
+#[must_use]
+pub fn palette() -> [Vec<StyledContent<String>>; 2] {
+    [0..8u8, 8..16u8].map(|range| {
+        range
+            .map(|x| "   ".to_owned().on(Color::AnsiValue(x)))
+            .collect()
+    })
 }
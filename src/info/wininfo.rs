@@ -35,8 +35,7 @@ use winreg::{
     RegKey,
 };
 
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
+use crate::info::{Battery, Disk, Memory, OSInfo, Uptime};
 
 #[derive(Default)]
 pub struct WindowsInfo {
@@ -233,16 +232,10 @@ impl OSInfo for WindowsInfo {
         .unwrap()
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
-        let uptime = time::Duration::milliseconds(GetTickCount64().try_into().ok()?).to_string();
-        Some(
-            uptime
-                .split_inclusive('m')
-                .next()?
-                .replace('h', " hours, ")
-                .replace('m', " mins")
-                .into(),
-        )
+    fn uptime(&self) -> Option<Uptime> {
+        Some(Uptime {
+            seconds: GetTickCount64() / 1000,
+        })
     }
 
     fn ip(&self) -> Vec<ArcStr> {
@@ -360,39 +353,36 @@ impl OSInfo for WindowsInfo {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         let result = GlobalMemoryStatusEx().ok()?;
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format(result.ullTotalPhys - result.ullAvailPhys, 2),
-            bytecount_format(result.ullTotalPhys, 2),
-        ))
+        Some(Memory {
+            used: result.ullTotalPhys - result.ullAvailPhys,
+            total: result.ullTotalPhys,
+        })
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<Disk> {
         let q = GetLogicalDriveStrings();
         q.map_or(Vec::new(), |c| {
             c.par_iter()
                 .filter_map(|x| {
-                    Some((ArcStr::from(x), {
-                        let var_name = 0xDEAD;
-                        let mut total: Option<u64> = Some(var_name);
-                        let var_name = 0xDEAD;
-                        let mut free: Option<u64> = Some(var_name);
-                        GetDiskFreeSpaceEx(Some(x), None, total.as_mut(), free.as_mut()).ok()?;
-                        arcstr::format!(
-                            "{} / {}",
-                            bytecount_format(total? - free?, 0),
-                            bytecount_format(total?, 0)
-                        )
-                    }))
+                    let var_name = 0xDEAD;
+                    let mut total: Option<u64> = Some(var_name);
+                    let var_name = 0xDEAD;
+                    let mut free: Option<u64> = Some(var_name);
+                    GetDiskFreeSpaceEx(Some(x), None, total.as_mut(), free.as_mut()).ok()?;
+                    Some(Disk {
+                        mount: ArcStr::from(x),
+                        used: total? - free?,
+                        total: total?,
+                    })
                 })
                 .collect()
         })
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -0,0 +1,259 @@
+//! Arrangement of the logo and system information into lines that are printed one after another
+
+use std::iter;
+
+use crossterm::style::{ContentStyle, StyledContent};
+use itertools::Itertools;
+
+use crate::config::Layout;
+
+/// A line of text made of pieces in different styles
+pub type Line = Vec<StyledContent<String>>;
+
+/// Columns between the logo and the system information
+const GAP: usize = 3;
+/// Fewest columns left for the system information next to the logo, below which it is placed
+/// under the logo instead
+const MIN_INFO_WIDTH: usize = 20;
+
+/// Columns `ch` takes up in a terminal: none for control characters and combining marks, two for
+/// wide characters like CJK ideographs and emoji, and one for everything else
+#[must_use]
+pub const fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x00..=0x1F
+        | 0x7F..=0x9F
+        | 0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Columns `text` takes up in a terminal, see [`char_width`]
+#[must_use]
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Columns taken up by `line`
+#[must_use]
+pub fn width(line: &Line) -> usize {
+    line.iter().map(|piece| text_width(piece.content())).sum()
+}
+
+/// Split styled pieces of text, which can span several lines or share one, into lines
+#[must_use]
+pub fn split_lines(pieces: impl IntoIterator<Item = StyledContent<String>>) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut current = Line::new();
+    for piece in pieces {
+        let mut parts = piece.content().split('\n').peekable();
+        while let Some(part) = parts.next() {
+            if !part.is_empty() {
+                current.push(StyledContent::new(*piece.style(), part.to_owned()));
+            }
+            if parts.peek().is_some() {
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Shorten `line` to at most `max` columns, ending it with an ellipsis if anything is cut off
+#[must_use]
+pub fn truncate(line: Line, max: usize) -> Line {
+    if width(&line) <= max {
+        return line;
+    }
+    if max == 0 {
+        return Line::new();
+    }
+    // Leave room for the ellipsis
+    let mut remaining = max - 1;
+    let mut style = ContentStyle::default();
+    let mut truncated = Line::new();
+    for piece in line {
+        style = *piece.style();
+        let mut text = String::new();
+        for ch in piece.content().chars() {
+            // Stop before a wide character that only half fits
+            let Some(left) = remaining.checked_sub(char_width(ch)) else {
+                remaining = 0;
+                break;
+            };
+            remaining = left;
+            text.push(ch);
+        }
+        if !text.is_empty() {
+            truncated.push(StyledContent::new(style, text));
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+    truncated.push(StyledContent::new(style, "…".to_owned()));
+    truncated
+}
+
+/// Arrange the logo and system information lines as `layout` asks, fitting every line into
+/// `max_width` columns if given, see [`columns`]
+///
+/// Side by side layouts fall back to the logo on top when there isn't room for both columns
+#[must_use]
+pub fn arrange(
+    layout: Layout,
+    logo: Vec<Line>,
+    info: Vec<Line>,
+    max_width: Option<usize>,
+) -> Vec<Line> {
+    let max_width = columns(max_width);
+    let logo_width = logo.iter().map(width).max().unwrap_or_default();
+    let (layout, info_max_width) = resolve(layout, logo_width, max_width);
+    let fit = |lines: Vec<Line>, max| {
+        lines
+            .into_iter()
+            .map(move |line| truncate(line, max))
+            .collect::<Vec<_>>()
+    };
+    match layout {
+        Layout::LogoLeft | Layout::LogoRight => {
+            let info = fit(info, info_max_width);
+            let info_width = info.iter().map(width).max().unwrap_or_default();
+            let height = logo.len().max(info.len());
+            let (logo, info) = (center(logo, height), center(info, height));
+            logo.into_iter()
+                .zip(info)
+                .map(|(logo_line, info_line)| {
+                    if layout == Layout::LogoLeft {
+                        join(logo_line, logo_width, info_line)
+                    } else {
+                        join(info_line, info_width, logo_line)
+                    }
+                })
+                .collect()
+        }
+        Layout::LogoTop => {
+            let mut lines = fit(logo, max_width);
+            lines.push(Line::new());
+            lines.extend(fit(info, max_width));
+            lines
+        }
+        Layout::NoLogo => fit(info, max_width),
+    }
+}
+
+/// Like [`arrange`], but yields each line as soon as the system information for it is available
+///
+/// The logo and the system information are aligned at the top instead of centered, as the number
+/// of information lines is not known up front. The logo on the right depends on the width of the
+/// widest information line, so that layout waits for all of them
+pub fn arrange_streaming<'a>(
+    layout: Layout,
+    logo: Vec<Line>,
+    info: impl Iterator<Item = Line> + 'a,
+    max_width: Option<usize>,
+) -> Box<dyn Iterator<Item = Line> + 'a> {
+    let columns = columns(max_width);
+    let logo_width = logo.iter().map(width).max().unwrap_or_default();
+    let (resolved, info_max_width) = resolve(layout, logo_width, columns);
+    match resolved {
+        Layout::LogoLeft => Box::new(
+            logo.into_iter()
+                .zip_longest(info.map(move |line| truncate(line, info_max_width)))
+                .map(move |lines| {
+                    let (logo_line, info_line) = lines.or_default();
+                    join(logo_line, logo_width, info_line)
+                }),
+        ),
+        Layout::LogoRight => Box::new(arrange(layout, logo, info.collect(), max_width).into_iter()),
+        Layout::LogoTop => Box::new(
+            logo.into_iter()
+                .chain(iter::once(Line::new()))
+                .chain(info)
+                .map(move |line| truncate(line, columns)),
+        ),
+        Layout::NoLogo => Box::new(info.map(move |line| truncate(line, columns))),
+    }
+}
+
+/// Whether a logo of `width` by `height` characters fits into a terminal of `size` columns and rows
+/// with `layout`, leaving enough room for the system information next to it
+#[must_use]
+pub const fn fits(layout: Layout, width: usize, height: usize, size: (usize, usize)) -> bool {
+    let (columns, rows) = size;
+    match layout {
+        Layout::LogoLeft | Layout::LogoRight => {
+            width + GAP + MIN_INFO_WIDTH <= columns && height <= rows
+        }
+        Layout::LogoTop => width <= columns && height <= rows,
+        Layout::NoLogo => true,
+    }
+}
+
+/// Columns available for the output, without a limit if `max_width` is `None` or zero, which is
+/// what some terminals report when their size is unknown
+fn columns(max_width: Option<usize>) -> usize {
+    max_width.filter(|width| *width > 0).unwrap_or(usize::MAX)
+}
+
+/// The layout to use for a logo `logo_width` columns wide, along with the columns left for the
+/// system information next to the logo
+const fn resolve(layout: Layout, logo_width: usize, max_width: usize) -> (Layout, usize) {
+    let info_max_width = max_width.saturating_sub(logo_width + GAP);
+    match layout {
+        Layout::LogoLeft | Layout::LogoRight if info_max_width < MIN_INFO_WIDTH => {
+            (Layout::LogoTop, info_max_width)
+        }
+        layout => (layout, info_max_width),
+    }
+}
+
+/// Add empty lines above and below `lines` so that it is centered in a column of `height` lines
+fn center(lines: Vec<Line>, height: usize) -> Vec<Line> {
+    let top = height.saturating_sub(lines.len()) / 2;
+    let mut centered = vec![Line::new(); top];
+    centered.extend(lines);
+    centered.resize(height.max(centered.len()), Line::new());
+    centered
+}
+
+/// Put `right` after `left`, with `left` padded to `left_width` columns and a gap in between
+fn join(mut left: Line, left_width: usize, right: Line) -> Line {
+    if width(&right) == 0 {
+        return left;
+    }
+    let padding = left_width.saturating_sub(width(&left)) + GAP;
+    left.push(StyledContent::new(
+        ContentStyle::default(),
+        " ".repeat(padding),
+    ));
+    left.extend(right);
+    left
+}
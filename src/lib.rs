@@ -4,7 +4,16 @@
 #![warn(clippy::style)]
 #![allow(clippy::cast_precision_loss)]
 
+pub mod color;
 pub mod colorizer;
 pub mod config;
+pub mod custom;
 pub mod info;
+pub mod layout;
+pub mod lint;
+pub mod setup;
+pub mod terminal;
 pub mod util;
+
+#[cfg(test)]
+mod tests;
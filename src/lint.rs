@@ -0,0 +1,114 @@
+//! Checks for icon files in the format of `data/icons.yaml`
+
+use std::{collections::BTreeSet, fmt};
+
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    layout::text_width,
+    util::{entry_names, AsciiArtUnprocessed, ASCII_REGEX},
+};
+
+/// A problem with an icon found by [`lint_icons`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// First name of the icon, or its position in the file if it has no name
+    pub icon: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.icon, self.message)
+    }
+}
+
+/// Check every icon in a YAML list of icons
+///
+/// This reports color markers without a color, a `width` other than the width of the widest line,
+/// names used by more than one icon, and whitespace past the end of the icon. Widths are measured
+/// in terminal columns, see [`text_width`], after removing the `${cN}` color markers
+///
+/// # Errors
+///
+/// This function will return an error if `yaml` is not a list
+pub fn lint_icons(yaml: &str) -> anyhow::Result<Vec<Issue>> {
+    let entries: Vec<serde_yaml::Value> = serde_yaml::from_str(yaml)?;
+    let mut issues = Vec::new();
+    // Icon that first used each lowercase name
+    let mut names = FxHashMap::default();
+    for (idx, entry) in entries.iter().enumerate() {
+        let icon = entry_names(entry)
+            .next()
+            .map_or_else(|| format!("#{}", idx + 1), ToOwned::to_owned);
+        let mut issue = |message| {
+            issues.push(Issue {
+                icon: icon.clone(),
+                message,
+            });
+        };
+        for name in entry_names(entry).map(str::to_lowercase) {
+            match names.get(&name) {
+                Some(other) if *other != idx => issue(format!(
+                    "name \"{name}\" is already used by icon #{}",
+                    other + 1
+                )),
+                _ => {
+                    names.insert(name, idx);
+                }
+            }
+        }
+        let art = match serde_yaml::from_value::<AsciiArtUnprocessed>(entry.clone()) {
+            Ok(art) => art,
+            Err(err) => {
+                issue(format!("invalid icon: {err}"));
+                continue;
+            }
+        };
+
+        let undefined: BTreeSet<&str> = ASCII_REGEX
+            .captures_iter(&art.art)
+            .filter(|captures| {
+                captures[1]
+                    .parse::<usize>()
+                    .map_or(true, |color| color == 0 || color > art.colors.len())
+            })
+            .map(|captures| captures.get(0).map_or("", |x| x.as_str()))
+            .collect();
+        for marker in undefined {
+            issue(format!(
+                "{marker} has no color, only {} defined",
+                art.colors.len()
+            ));
+        }
+
+        let lines: Vec<String> = art
+            .art
+            .lines()
+            .map(|line| ASCII_REGEX.replace_all(line, "").into_owned())
+            .collect();
+        let width = usize::from(art.width);
+        let measured = lines
+            .iter()
+            .map(|line| text_width(line.trim_end()))
+            .max()
+            .unwrap_or_default();
+        if measured != width {
+            issue(format!(
+                "width is {width} but the widest line is {measured} columns wide"
+            ));
+        }
+        let trailing = lines
+            .iter()
+            .positions(|line| text_width(line) > width.max(text_width(line.trim_end())))
+            .map(|idx| idx + 1)
+            .join(", ");
+        if !trailing.is_empty() {
+            issue(format!(
+                "trailing whitespace past the width of {width} on lines {trailing}"
+            ));
+        }
+    }
+    Ok(issues)
+}
@@ -5,130 +5,596 @@
 
 use anyhow::{anyhow, Ok, Result};
 use arcstr::ArcStr;
-use clap::Parser;
-use crossterm::{
-    cursor::{position, MoveTo, MoveToColumn, MoveToRow},
-    style::{Color, PrintStyledContent, Stylize},
-    ExecutableCommand,
-};
+use clap::{Parser, ValueEnum};
+use crossterm::style::{Color, StyledContent, Stylize};
 use directories::ProjectDirs;
+use itertools::Itertools;
 use mirafetch::{
+    color::quantize,
     colorizer::{Colorizer, DefaultColors, FlagColors},
-    config::{Config, Orientation},
-    util::{get_colorscheme, get_icon, AsciiArt},
+    config::{ColorMode, Command, Config, Format, LightDark, LogoSize, Render, Theme},
+    custom::{self, CustomModule, CustomProbe},
+    info::{self, Collector, CustomValue, Field, InOrder, Probe, SystemInfo},
+    layout::{self, Line},
+    lint::lint_icons,
+    setup, terminal,
+    util::{
+        get_colorscheme, get_colorschemes, get_icon, get_icons, get_small_variant, load_icon_dir,
+        load_icon_file, load_scheme_file, parse_schemes, AsciiArt, NotFound,
+    },
+};
+use rustc_hash::FxHashMap;
+use std::{
+    fs,
+    io::{stdin, stdout, ErrorKind, IsTerminal, Write},
+    iter::{self, zip},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
 };
-use std::{fmt::Display, fs, io::stdout, process::ExitCode, sync::Arc};
 use std::{
     sync::mpsc,
     thread::{self},
+    time::{Duration, Instant},
 };
-mod info;
-mod util;
 
-fn main() -> anyhow::Result<std::process::ExitCode> {
-    let settings = load_settings_file()?.with_config(Config::parse());
-    let scheme = get_colorscheme_from_settings(&settings);
+fn main() -> ExitCode {
+    run().unwrap_or_else(|err| {
+        // Errors carrying an exit code have already been reported
+        if let Some(code) = err.downcast_ref::<exitcode::ExitCode>() {
+            return exit_code(*code);
+        }
+        eprintln!("Error: {err:#}");
+        if err.is::<NotFound>() {
+            exit_code(exitcode::USAGE)
+        } else {
+            ExitCode::FAILURE
+        }
+    })
+}
+
+fn exit_code(code: exitcode::ExitCode) -> ExitCode {
+    u8::try_from(code).map_or(ExitCode::FAILURE, ExitCode::from)
+}
 
+fn run() -> Result<ExitCode> {
+    let config_dir = get_config_dir()?;
+    let args = Config::parse();
+    if let Some(Command::LintIcons { file }) = &args.command {
+        return lint_icon_file(file);
+    }
+    let mut settings = load_settings_file(&config_dir)?.with_config(args);
+    if settings.list_schemes || settings.list_icons {
+        ignore_broken_pipe(list(&settings, &config_dir))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if settings.setup || offer_setup(&settings, &config_dir) {
+        run_setup(&settings, &config_dir)?;
+        settings = load_settings_file(&config_dir)?.with_config(Config::parse());
+    }
+    let modules = settings
+        .modules
+        .clone()
+        .unwrap_or_else(|| Field::ALL.to_vec());
+    let start = Instant::now();
     let (tx, rx) = mpsc::channel();
-    let id = info::get_id();
-    let logo: AsciiArt = get_icon(&get_os_id(&settings, &id))?;
-    let colored_logo = colorize_logo(settings.orientation, &scheme, &logo)?;
+    let probe_modules = modules.clone();
     thread::spawn(move || {
-        info::get_async(&tx);
+        info::get_async(&tx, &probe_modules);
     });
+    let timeout = settings.timeout.map(Duration::from_millis);
+    let deadline = timeout.map(|timeout| start + timeout);
+    let custom_rx = spawn_custom(&settings.custom, deadline);
+    let mut custom_probes = Vec::new();
+    let mut timings = Vec::with_capacity(modules.len());
+    let probes = Collector::new(rx, &modules, timeout)
+        .inspect(|probe| timings.push((probe.field, probe.elapsed)));
+
+    if settings.format == Some(Format::Json) {
+        let mut system_info = SystemInfo {
+            id: info::get_id(),
+            ..SystemInfo::default()
+        };
+        system_info.extend(probes.filter_map(|probe| probe.entry));
+        custom_probes = collect_custom(&custom_rx, settings.custom.len(), deadline);
+        system_info.custom = custom_values(&settings.custom, &custom_probes);
+        serde_json::to_writer_pretty(stdout(), &system_info)?;
+        println!();
+    } else {
+        let plain = settings.no_color || terminal::plain_output();
+        // Plain output has no colors to adjust, so skip querying the terminal background
+        let flag = get_colorscheme_from_settings(&settings, &config_dir)?
+            .filter(|_| !plain)
+            .map(|scheme| flag_colors(&settings, scheme))
+            .transpose()?;
+        let logo = load_logo(&settings, &config_dir)?;
+        let colored_logo = if plain {
+            logo.art
+                .iter()
+                .map(|(_, text)| text.clone().stylize())
+                .collect()
+        } else {
+            colorize_logo(flag.as_ref(), &logo, color_mode(&settings))
+        };
+        let title = info::get_title();
+        let probes: Box<dyn Iterator<Item = Probe>> = match settings.render.unwrap_or_default() {
+            Render::Ordered => Box::new(InOrder::new(probes, &modules)),
+            Render::Streaming => Box::new(probes),
+        };
+        let lines = probes
+            .flat_map(|probe| {
+                probe.lines(
+                    settings.labels.get(&probe.field).map(String::as_str),
+                    settings.formats.get(&probe.field).map(String::as_str),
+                )
+            })
+            .chain(
+                iter::once_with(|| {
+                    custom_probes = collect_custom(&custom_rx, settings.custom.len(), deadline);
+                    custom_lines(&settings.custom, &custom_probes)
+                })
+                .flatten(),
+            );
+        let colors = (!plain).then(|| {
+            let theme_colors = flag
+                .as_ref()
+                .map_or_else(|| logo.colors.clone().into(), FlagColors::colors);
+            InfoColors::new(&settings.theme, &theme_colors, color_mode(&settings))
+        });
 
-    // Show system info
-    display(colored_logo, rx, &logo).ok();
+        let info = info_lines(&title, lines, settings.theme.separator(), colors.as_ref())
+            .chain((!plain).then(info::palette).into_iter().flatten());
+
+        // Show system info
+        let lines = arrange(&settings, layout::split_lines(colored_logo), info);
+        ignore_broken_pipe(display(lines))?;
+    }
+
+    if settings.timings {
+        timings.sort_by_key(|(field, _)| modules.iter().position(|x| x == field));
+        print_timings(&timings, &settings.custom, &custom_probes, start.elapsed());
+    }
 
     Ok(ExitCode::SUCCESS)
 }
 
-fn get_os_id(settings: &Config, default: &impl ToString) -> impl ToString {
-    settings
-        .icon_name
-        .as_ref()
-        .unwrap_or(&default.to_string())
-        .to_owned()
+fn get_colorscheme_from_settings(
+    settings: &Config,
+    config_dir: &Path,
+) -> Result<Option<Arc<[Color]>>> {
+    let Some(name) = &settings.scheme_name else {
+        return Ok(None);
+    };
+    get_colorscheme(name, &load_schemes(settings, config_dir)?).map(Some)
+}
+
+/// Load the user defined schemes from `flags.toml` in the config directory and from the settings
+fn load_schemes(settings: &Config, config_dir: &Path) -> Result<FxHashMap<String, Arc<[Color]>>> {
+    let scheme_path = config_dir.join("flags.toml");
+    let mut schemes = if scheme_path.exists() {
+        load_scheme_file(&scheme_path)?
+    } else {
+        FxHashMap::default()
+    };
+    schemes.extend(parse_schemes(&settings.schemes)?);
+    Ok(schemes)
 }
 
-fn get_colorscheme_from_settings(settings: &Config) -> Option<Arc<[Color]>> {
-    let scheme: Option<Arc<[Color]>> = settings
-        .scheme_name
-        .as_ref()
-        .map_or_else(|| None, |name| Some(get_colorscheme(name)));
-    scheme
+/// Print the problems `lint-icons` finds in an icon file, exiting with an error if there are any
+fn lint_icon_file(path: &Path) -> Result<ExitCode> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Could not read icon file {}: {err}", path.display()))?;
+    let issues = lint_icons(&contents)
+        .map_err(|err| anyhow!("Invalid icon file {}: {err}", path.display()))?;
+    for issue in &issues {
+        println!("{issue}");
+    }
+    if issues.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprintln!("Found {} problems in {}", issues.len(), path.display());
+        Ok(exit_code(exitcode::DATAERR))
+    }
+}
+
+/// Stop quietly when the output is piped into eg `head`, which closes the pipe early
+fn ignore_broken_pipe(result: Result<()>) -> Result<()> {
+    match result {
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
 }
 
-fn load_settings_file() -> Result<Config, anyhow::Error> {
-    let proj_dir = ProjectDirs::from("", "", "Mirafetch");
-    let settings = proj_dir
+/// Print the schemes and icons requested by `--list-schemes` and `--list-icons`
+fn list(settings: &Config, config_dir: &Path) -> Result<()> {
+    let mut out = stdout().lock();
+    let plain = settings.no_color || terminal::plain_output();
+    if settings.list_schemes {
+        let schemes = get_colorschemes(&load_schemes(settings, config_dir)?);
+        // Schemes can only be previewed in color
+        let preview = settings.preview && !plain;
+        list_schemes(&mut out, &schemes, preview, color_mode(settings))?;
+    }
+    if settings.list_icons {
+        let icons = get_icons(&load_icon_dir(&config_dir.join("icons"))?);
+        let color_mode = (!plain).then(|| color_mode(settings));
+        list_icons(&mut out, &icons, settings.preview, color_mode)?;
+    }
+    Ok(())
+}
+
+/// Print the name of each scheme, optionally followed by a bar with a block for each stripe
+fn list_schemes(
+    out: &mut impl Write,
+    schemes: &[(String, Arc<[Color]>)],
+    preview: bool,
+    color_mode: ColorMode,
+) -> Result<()> {
+    let width = schemes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, colors) in schemes {
+        if preview {
+            let bar: String = colors
+                .iter()
+                .map(|color| "███".with(quantize(*color, color_mode)).to_string())
+                .collect();
+            writeln!(out, "{name:width$} {bar}")?;
+        } else {
+            writeln!(out, "{name}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Print the names of each icon, optionally followed by the icon in its default colors converted
+/// to `color_mode`, or without colors when `color_mode` is `None`
+fn list_icons(
+    out: &mut impl Write,
+    icons: &[AsciiArt],
+    preview: bool,
+    color_mode: Option<ColorMode>,
+) -> Result<()> {
+    for icon in icons {
+        writeln!(out, "{}", icon.name.iter().unique().join(", "))?;
+        if preview {
+            let colorizer = DefaultColors {
+                color_mode: color_mode.unwrap_or_default(),
+            };
+            for line in colorizer.colorize(icon) {
+                if color_mode.is_some() {
+                    write!(out, "{line}")?;
+                } else {
+                    write!(out, "{}", line.content())?;
+                }
+            }
+            writeln!(out, "\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// File in the config directory recording that the setup wizard has been offered
+const SETUP_MARKER: &str = ".setup_offered";
+
+/// Whether to offer the setup wizard without `--setup`, which happens once when there is no config
+/// file yet and mirafetch is run interactively. Offering it is recorded, so that quitting the
+/// wizard without saving does not bring it up again on the next run
+fn offer_setup(settings: &Config, config_dir: &Path) -> bool {
+    let marker = config_dir.join(SETUP_MARKER);
+    let offer = settings.format != Some(Format::Json)
+        && !config_dir.join("config.toml").exists()
+        && !marker.exists()
+        && stdin().is_terminal()
+        && stdout().is_terminal();
+    if offer {
+        // Not being able to write the marker only means the wizard is offered again
+        fs::create_dir_all(config_dir)
+            .and_then(|()| fs::write(marker, ""))
+            .ok();
+    }
+    offer
+}
+
+/// Run the setup wizard on the logo for this system and save the chosen settings
+fn run_setup(settings: &Config, config_dir: &Path) -> Result<()> {
+    let logo = load_logo(settings, config_dir)?;
+    let schemes = get_colorschemes(&load_schemes(settings, config_dir)?);
+    let config_path = config_dir.join("config.toml");
+    if let Some(selection) = setup::run(&logo, &schemes, settings, &config_path)? {
+        setup::save(&config_path, &selection)?;
+    }
+    Ok(())
+}
+
+fn get_config_dir() -> Result<PathBuf, anyhow::Error> {
+    ProjectDirs::from("", "", "Mirafetch")
+        .map(|dir| dir.config_dir().to_path_buf())
         .ok_or_else(|| {
             anyhow!(
                 "Could not find a project directory for Mirafetch. Please report this as a bug.",
             )
         })
-        .and_then(|dir| {
-            let config_path = dir.config_dir().join("config.toml");
-            if !config_path.exists() {
-                return anyhow::Ok(Config::default());
-            };
-            let config_file = fs::read_to_string(config_path)?;
-            toml::from_str::<Config>(&config_file).map_err(|err| {
-                eprintln!("Invalid config: {err}");
-                anyhow!(exitcode::CONFIG)
-            })
-        })?;
-    Ok(settings)
+}
+
+fn load_settings_file(config_dir: &Path) -> Result<Config, anyhow::Error> {
+    let config_path = config_dir.join("config.toml");
+    if !config_path.exists() {
+        return anyhow::Ok(Config::default());
+    }
+    let config_file = fs::read_to_string(config_path)?;
+    toml::from_str::<Config>(&config_file).map_err(|err| {
+        eprintln!("Invalid config: {err}");
+        anyhow!(exitcode::CONFIG)
+    })
+}
+
+fn load_logo(settings: &Config, config_dir: &Path) -> Result<AsciiArt> {
+    if let Some(path) = &settings.icon_path {
+        return load_icon_file(path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No icon found in {}", path.display()));
+    }
+    let extra = load_icon_dir(&config_dir.join("icons"))?;
+    let logo = find_logo(settings, &extra)?;
+    let small = match settings.logo_size.unwrap_or_default() {
+        LogoSize::Small => true,
+        LogoSize::Full => false,
+        LogoSize::Auto => terminal::size().is_some_and(|size| {
+            let (width, height) = (usize::from(logo.width), usize::from(logo.height));
+            !layout::fits(settings.layout.unwrap_or_default(), width, height, size)
+        }),
+    };
+    Ok(small
+        .then(|| get_small_variant(&logo, &extra))
+        .flatten()
+        .unwrap_or(logo))
+}
+
+/// Find the logo named in the settings, or the logo for this system
+fn find_logo(settings: &Config, extra: &[AsciiArt]) -> Result<AsciiArt> {
+    if let Some(name) = &settings.icon_name {
+        return get_icon(name, extra);
+    }
+    // Fall back to the systems this one is based on, eg ubuntu and debian for an Ubuntu derivative,
+    // reporting the most specific name if none of them has an icon
+    let mut not_found = None;
+    for id in info::get_icon_ids() {
+        match get_icon(&id, extra) {
+            Err(err) if err.is::<NotFound>() => {
+                not_found.get_or_insert(err);
+            }
+            icon => return icon,
+        }
+    }
+    Err(not_found.unwrap_or_else(|| anyhow!("No icon found for this system")))
+}
+
+fn color_mode(settings: &Config) -> ColorMode {
+    settings.color_mode.unwrap_or_else(terminal::color_mode)
+}
+
+fn flag_colors(settings: &Config, scheme: Arc<[Color]>) -> Result<FlagColors> {
+    if let Some(lightness) = settings.lightness.filter(|x| !(0.0..=1.0).contains(x)) {
+        eprintln!("Invalid config: lightness must be between 0.0 and 1.0, not {lightness}");
+        return Err(anyhow!(exitcode::CONFIG));
+    }
+    Ok(FlagColors {
+        color_scheme: scheme,
+        orientation: settings
+            .orientation
+            .ok_or_else(|| anyhow!("Missing Orientation"))?,
+        light_dark: settings.light_dark.map(LightDark::resolve),
+        lightness: settings.lightness,
+        color_mode: color_mode(settings),
+    })
 }
 
 fn colorize_logo(
-    orientation: Option<Orientation>,
-    scheme: &Option<Arc<[Color]>>,
+    flag: Option<&FlagColors>,
     logo: &AsciiArt,
-) -> Result<impl IntoIterator<Item = crossterm::style::StyledContent<impl Display>>, anyhow::Error>
-{
-    let colorizer = match scheme {
-        None => Box::new(DefaultColors {}) as Box<dyn Colorizer>,
-        Some(colors) => Box::new(FlagColors {
-            color_scheme: colors.clone(),
-            orientation: orientation.ok_or_else(|| anyhow!("Missing Orientation"))?,
-        }) as Box<dyn Colorizer>,
+    color_mode: ColorMode,
+) -> Vec<StyledContent<String>> {
+    flag.map_or_else(
+        || DefaultColors { color_mode }.colorize(logo),
+        |flag| flag.colorize(logo),
+    )
+}
+
+/// Run the custom modules in the background, killing commands still running at `deadline`
+fn spawn_custom(
+    modules: &[CustomModule],
+    deadline: Option<Instant>,
+) -> mpsc::Receiver<CustomProbe> {
+    let (tx, rx) = mpsc::channel();
+    let modules = modules.to_vec();
+    thread::spawn(move || {
+        custom::get_async(&tx, &modules, deadline);
+    });
+    rx
+}
+
+/// Results of the custom modules in the order of the module list, `None` for modules which did
+/// not report back by `deadline`
+fn collect_custom(
+    rx: &mpsc::Receiver<CustomProbe>,
+    count: usize,
+    deadline: Option<Instant>,
+) -> Vec<Option<CustomProbe>> {
+    // Commands still running at the deadline report back once they have been killed
+    let deadline = deadline.map(|deadline| deadline + custom::KILL_TIME);
+    let mut results = vec![None; count];
+    for _ in 0..count {
+        let received = deadline.map_or_else(
+            || rx.recv().ok(),
+            |deadline| {
+                rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok()
+            },
+        );
+        let Some(probe) = received else {
+            break;
+        };
+        let idx = probe.idx;
+        results[idx] = Some(probe);
+    }
+    results
+}
+
+/// Label and value pairs for the custom modules, showing modules which timed out as unavailable
+fn custom_lines(modules: &[CustomModule], probes: &[Option<CustomProbe>]) -> Vec<(ArcStr, ArcStr)> {
+    zip(modules, probes)
+        .filter_map(|(module, probe)| {
+            let value = match probe {
+                Some(probe) if !probe.timed_out() => probe.value.clone()?,
+                _ => arcstr::literal!("unavailable"),
+            };
+            Some((ArcStr::from(module.label.as_str()), value))
+        })
+        .collect()
+}
+
+/// Values of the custom modules which could be read, for the JSON output
+fn custom_values(modules: &[CustomModule], probes: &[Option<CustomProbe>]) -> Vec<CustomValue> {
+    zip(modules, probes)
+        .filter_map(|(module, probe)| {
+            Some(CustomValue {
+                label: ArcStr::from(module.label.as_str()),
+                value: probe.as_ref()?.value.clone()?,
+            })
+        })
+        .collect()
+}
+
+/// Print how long each module took to stderr, followed by the custom modules named by their label
+fn print_timings(
+    timings: &[(Field, Option<Duration>)],
+    custom_modules: &[CustomModule],
+    custom_probes: &[Option<CustomProbe>],
+    total: Duration,
+) {
+    let custom_timings = zip(custom_modules, custom_probes)
+        .map(|(module, probe)| (module.label.clone(), probe.as_ref().and_then(|x| x.elapsed)));
+    let names = timings
+        .iter()
+        .map(|(field, elapsed)| {
+            (
+                field
+                    .to_possible_value()
+                    .map_or_else(String::new, |value| value.get_name().to_owned()),
+                *elapsed,
+            )
+        })
+        .chain(custom_timings)
+        .collect::<Vec<_>>();
+    let width = names.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, elapsed) in names {
+        match elapsed {
+            Some(elapsed) => eprintln!("{name:width$} {:>9.3} ms", elapsed.as_secs_f64() * 1000.0),
+            None => eprintln!("{name:width$} {:>12}", "timed out"),
+        }
+    }
+    eprintln!(
+        "{:width$} {:>9.3} ms",
+        "total",
+        total.as_secs_f64() * 1000.0
+    );
+}
+
+/// Colors of the system information next to the logo
+struct InfoColors {
+    title: Color,
+    label: Color,
+    value: Color,
+}
+
+impl InfoColors {
+    fn new(theme: &Theme, theme_colors: &[Color], color_mode: ColorMode) -> Self {
+        Self {
+            title: quantize(theme.title_color(theme_colors), color_mode),
+            label: quantize(theme.label_color(theme_colors), color_mode),
+            value: quantize(theme.value_color(), color_mode),
+        }
+    }
+}
+
+/// Lines of system information below a title, or plain text when `colors` is `None`
+fn info_lines<'a>(
+    title: &str,
+    info: impl IntoIterator<Item = (ArcStr, ArcStr)> + 'a,
+    separator: &'a str,
+    colors: Option<&'a InfoColors>,
+) -> impl Iterator<Item = Line> + 'a {
+    let style = move |text: &str, color: fn(&InfoColors) -> Color, bold: bool| {
+        let styled = text.to_owned().stylize();
+        match colors {
+            Some(colors) if !text.is_empty() => {
+                let styled = styled.with(color(colors));
+                if bold {
+                    styled.bold()
+                } else {
+                    styled
+                }
+            }
+            _ => styled,
+        }
     };
+    [title.to_owned(), "-".repeat(title.len())]
+        .into_iter()
+        .map(move |line| vec![style(&line, |colors| colors.title, true)])
+        .chain(info.into_iter().map(move |(label, value)| {
+            let separator = if label.is_empty() || value.is_empty() {
+                ""
+            } else {
+                separator
+            };
+            vec![
+                style(&label, |colors| colors.label, true),
+                style(separator, |colors| colors.label, true),
+                style(&value, |colors| colors.value, false),
+            ]
+        }))
+}
 
-    Ok(colorizer.colorize(logo))
+/// Arrange the logo and system information for the terminal, yielding each line as soon as it is
+/// available when streaming
+fn arrange<'a>(
+    settings: &Config,
+    logo: Vec<Line>,
+    info: impl Iterator<Item = Line> + 'a,
+) -> Box<dyn Iterator<Item = Line> + 'a> {
+    let layout = settings.layout.unwrap_or_default();
+    let max_width = terminal::size().map(|(columns, _)| columns);
+    match settings.render.unwrap_or_default() {
+        Render::Ordered => {
+            Box::new(layout::arrange(layout, logo, info.collect(), max_width).into_iter())
+        }
+        Render::Streaming => layout::arrange_streaming(layout, logo, info, max_width),
+    }
 }
 
-/// Display the formatted logo and system information
+/// Print the arranged logo and system information, flushing each line as soon as it is available
 ///
 /// # Errors
 ///
-/// This function will return an error if the terminal settings (eg color, cursor position) cannot be modified
-fn display(
-    icon: impl IntoIterator<Item = crossterm::style::StyledContent<impl Display>>,
-    info: impl IntoIterator<Item = (ArcStr, ArcStr)>,
-    logo: &AsciiArt,
-) -> Result<(), anyhow::Error> {
-    let mut out = stdout();
-    // print icon
-    println!();
-    //let (_, top) = position()?;
-    for line in icon {
-        out.execute(PrintStyledContent(line))?;
-    }
-    let (_, logo_bottom) = position()?;
-    out.execute(MoveTo(0, logo_bottom - logo.height + 1))?;
-    // print system info
-    for (property, value) in info {
-        out.execute(MoveToColumn(logo.width + 3))?
-            .execute(PrintStyledContent(property.clone().bold().red()))?;
-        if !property.is_empty() && !value.is_empty() {
-            out.execute(PrintStyledContent(": ".bold().red()))?;
-        }
-        out.execute(PrintStyledContent(value.reset()))?;
-        println!();
+/// This function will return an error if writing to stdout fails
+fn display(lines: impl IntoIterator<Item = Line>) -> Result<()> {
+    let mut out = stdout().lock();
+    writeln!(out)?;
+    for line in lines {
+        let line: String = line.iter().map(ToString::to_string).collect();
+        writeln!(out, "{}", line.trim_end())?;
+        out.flush()?;
     }
-    let (_, info_bottom) = position()?;
-    out.execute(MoveToRow(std::cmp::max(info_bottom, logo_bottom)))?;
-    println!();
+    writeln!(out)?;
     Ok(())
 }
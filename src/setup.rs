@@ -0,0 +1,454 @@
+//! Interactive setup wizard, previewing flag schemes on the current logo before saving them to the
+//! config file
+
+use std::{
+    fs,
+    io::{stdout, Stdout, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Color, Print, PrintStyledContent, StyledContent, Stylize},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    colorizer::{Colorizer, FlagColors},
+    config::{ColorMode, Config, LightDark, Orientation},
+    util::AsciiArt,
+};
+
+/// Settings chosen in the setup wizard
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Selection {
+    pub scheme_name: String,
+    pub orientation: Orientation,
+    pub light_dark: LightDark,
+    /// Lightness of the flag colors, or `None` for the default of the terminal background
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_lightness"
+    )]
+    pub lightness: Option<f32>,
+}
+
+/// Serialize the lightness rounded to a percentage, as `0.4f32` would otherwise be saved as
+/// `0.4000000059604645`
+#[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+fn serialize_lightness<S: Serializer>(
+    lightness: &Option<f32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    lightness
+        .map(|x| (f64::from(x) * 100.0).round() / 100.0)
+        .serialize(serializer)
+}
+
+/// Flag brightness choices, `None` being the default for the terminal background
+const LIGHTNESS_OPTIONS: [Option<f32>; 8] = [
+    None,
+    Some(0.2),
+    Some(0.3),
+    Some(0.4),
+    Some(0.5),
+    Some(0.6),
+    Some(0.7),
+    Some(0.8),
+];
+
+/// Walk through choosing the terminal background, a flag scheme, its brightness and orientation,
+/// previewing each choice on `logo`. The choices start out at the `current` settings
+///
+/// Returns `None` if the user quits without saving
+///
+/// # Errors
+///
+/// This function will return an error if the terminal cannot be switched to raw mode or drawn to
+pub fn run(
+    logo: &AsciiArt,
+    schemes: &[(String, Arc<[Color]>)],
+    current: &Config,
+    config_path: &Path,
+) -> Result<Option<Selection>> {
+    if schemes.is_empty() {
+        return Ok(None);
+    }
+    // Ask the terminal before switching screens, so the answer is about the screen in use
+    let detected = current.light_dark.unwrap_or(LightDark::Auto).resolve();
+    let color_mode = current
+        .color_mode
+        .unwrap_or_else(crate::terminal::color_mode);
+    let _terminal = RawTerminal::enter()?;
+    let mut out = stdout();
+    let backgrounds = [LightDark::Dark, LightDark::Light];
+    let Some(light_dark) = choose_option(
+        &mut out,
+        "Is your terminal background dark or light?",
+        &backgrounds,
+        usize::from(detected == LightDark::Light),
+        |light_dark| format!("{light_dark:?}"),
+        0,
+        |_| Vec::new(),
+    )?
+    else {
+        return Ok(None);
+    };
+    let initial = current
+        .scheme_name
+        .as_ref()
+        .and_then(|name| schemes.iter().position(|(scheme, _)| scheme == name))
+        .unwrap_or_default();
+    let look = Look {
+        light_dark,
+        lightness: None,
+        color_mode,
+    };
+    let Some(scheme) = choose_scheme(&mut out, logo, schemes, initial, look)? else {
+        return Ok(None);
+    };
+    let (scheme_name, colors) = &schemes[scheme];
+    let orientation = current.orientation.unwrap_or(Orientation::Horizontal);
+    let Some(lightness) = choose_option(
+        &mut out,
+        "Choose the brightness of the flag",
+        &LIGHTNESS_OPTIONS,
+        LIGHTNESS_OPTIONS
+            .iter()
+            .position(|x| *x == current.lightness)
+            .unwrap_or_default(),
+        |lightness| {
+            lightness.map_or_else(
+                || String::from("Default"),
+                |lightness| format!("{:.0}%", lightness * 100.0),
+            )
+        },
+        logo.width,
+        |lightness| flag_colors(colors, orientation, Look { lightness, ..look }).colorize(logo),
+    )?
+    else {
+        return Ok(None);
+    };
+    let look = Look { lightness, ..look };
+    let orientations = [Orientation::Horizontal, Orientation::Vertical];
+    let Some(orientation) = choose_option(
+        &mut out,
+        "Choose the direction of the stripes",
+        &orientations,
+        usize::from(orientation == Orientation::Vertical),
+        |orientation| format!("{orientation:?}"),
+        logo.width,
+        |orientation| flag_colors(colors, orientation, look).colorize(logo),
+    )?
+    else {
+        return Ok(None);
+    };
+    let flag = flag_colors(colors, orientation, look);
+    if !confirm(&mut out, logo, &flag, config_path)? {
+        return Ok(None);
+    }
+    Ok(Some(Selection {
+        scheme_name: scheme_name.clone(),
+        orientation,
+        light_dark,
+        lightness,
+    }))
+}
+
+/// Save the selection to the config file at `path`, keeping any other settings already in it
+///
+/// # Errors
+///
+/// This function will return an error if the existing config file is invalid or cannot be written
+pub fn save(path: &Path, selection: &Selection) -> Result<()> {
+    let mut table = if path.exists() {
+        fs::read_to_string(path)?.parse::<toml::Table>()?
+    } else {
+        toml::Table::new()
+    };
+    if selection.lightness.is_none() {
+        table.remove("lightness");
+    }
+    table.extend(toml::Table::try_from(selection)?);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string(&table)?)?;
+    Ok(())
+}
+
+/// Raw mode and alternate screen for the wizard, restoring the terminal when dropped, including
+/// when the wizard exits with an error
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        let terminal = Self;
+        stdout().execute(EnterAlternateScreen)?.execute(Hide)?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        stdout()
+            .execute(Show)
+            .and_then(|out| out.execute(LeaveAlternateScreen))
+            .ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Quit,
+    Char(char),
+}
+
+/// Wait for the next key press
+fn read_key() -> Result<Key> {
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        return Ok(match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Key::Quit,
+            KeyCode::Up | KeyCode::Char('k') => Key::Up,
+            KeyCode::Down | KeyCode::Char('j') => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc | KeyCode::Char('q') => Key::Quit,
+            KeyCode::Char(ch) => Key::Char(ch),
+            _ => continue,
+        });
+    }
+}
+
+/// Pick a scheme from a scrolling list, with the logo colored by the highlighted scheme beside it
+fn choose_scheme(
+    out: &mut Stdout,
+    logo: &AsciiArt,
+    schemes: &[(String, Arc<[Color]>)],
+    initial: usize,
+    look: Look,
+) -> Result<Option<usize>> {
+    let name_width = schemes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let bar_width = schemes
+        .iter()
+        .map(|(_, colors)| colors.len() * 2)
+        .max()
+        .unwrap_or(0);
+    let list_width = u16::try_from(name_width + bar_width + 3).unwrap_or(u16::MAX);
+    let mut selected = initial;
+    let mut top = 0;
+    loop {
+        let (_, rows) = terminal::size()?;
+        let visible = usize::from(rows.saturating_sub(3)).max(1);
+        top = top.clamp(selected.saturating_sub(visible - 1), selected);
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print("Choose a flag: ↑/↓ to move, Enter to select, q to quit".bold())
+        )?;
+        for (row, (idx, (name, colors))) in
+            (2..).zip(schemes.iter().enumerate().skip(top).take(visible))
+        {
+            let name = format!("{name:name_width$}");
+            queue!(
+                out,
+                MoveTo(0, row),
+                PrintStyledContent(if idx == selected {
+                    name.reverse()
+                } else {
+                    name.stylize()
+                }),
+                Print(" ")
+            )?;
+            for color in flag_colors(colors, Orientation::Horizontal, look)
+                .colors()
+                .iter()
+            {
+                queue!(out, PrintStyledContent("██".with(*color)))?;
+            }
+        }
+        let preview =
+            flag_colors(&schemes[selected].1, Orientation::Horizontal, look).colorize(logo);
+        draw_art(out, preview, list_width, 2, rows)?;
+        out.flush()?;
+        let page = visible.saturating_sub(1).max(1);
+        selected = match read_key()? {
+            Key::Up => selected.saturating_sub(1),
+            Key::Down => (selected + 1).min(schemes.len() - 1),
+            Key::PageUp => selected.saturating_sub(page),
+            Key::PageDown => (selected + page).min(schemes.len() - 1),
+            Key::Home => 0,
+            Key::End => schemes.len() - 1,
+            Key::Enter => return Ok(Some(selected)),
+            Key::Quit => return Ok(None),
+            // Jump to the next scheme starting with the typed letter
+            Key::Char(ch) => (1..schemes.len())
+                .map(|offset| (selected + offset) % schemes.len())
+                .find(|idx| schemes[*idx].0.starts_with(ch))
+                .unwrap_or(selected),
+            Key::Left | Key::Right => selected,
+        };
+    }
+}
+
+/// Pick one of `options` with ←/→, showing the preview of each option below its name, side by
+/// side when they fit
+fn choose_option<T: Copy>(
+    out: &mut Stdout,
+    title: &str,
+    options: &[T],
+    initial: usize,
+    label: impl Fn(T) -> String,
+    preview_width: u16,
+    preview: impl Fn(T) -> Vec<StyledContent<String>>,
+) -> Result<Option<T>> {
+    let width = options
+        .iter()
+        .map(|option| u16::try_from(label(*option).len()).unwrap_or(u16::MAX))
+        .max()
+        .unwrap_or_default()
+        .max(preview_width)
+        + 4;
+    let mut selected = initial;
+    loop {
+        let (columns, rows) = terminal::size()?;
+        let side_by_side = usize::from(width) * options.len() <= usize::from(columns);
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print(format!("{title}: ←/→ to move, Enter to select, q to quit").bold())
+        )?;
+        for (idx, option) in options.iter().enumerate() {
+            if !side_by_side && idx != selected {
+                continue;
+            }
+            let column = if side_by_side {
+                u16::try_from(idx).unwrap_or_default() * width
+            } else {
+                0
+            };
+            let label = label(*option);
+            queue!(
+                out,
+                MoveTo(column, 2),
+                PrintStyledContent(if idx == selected {
+                    label.reverse()
+                } else {
+                    label.stylize()
+                })
+            )?;
+            draw_art(out, preview(*option), column, 4, rows)?;
+        }
+        out.flush()?;
+        selected = match read_key()? {
+            Key::Left | Key::Up => selected.saturating_sub(1),
+            Key::Right | Key::Down => (selected + 1).min(options.len() - 1),
+            Key::Enter => return Ok(Some(options[selected])),
+            Key::Quit => return Ok(None),
+            _ => selected,
+        };
+    }
+}
+
+/// Show the final result and ask whether to save it
+fn confirm(
+    out: &mut Stdout,
+    logo: &AsciiArt,
+    flag: &FlagColors,
+    config_path: &Path,
+) -> Result<bool> {
+    let (_, rows) = terminal::size()?;
+    queue!(
+        out,
+        Clear(ClearType::All),
+        MoveTo(0, 0),
+        Print(format!("Save to {}? [Y/n]", config_path.display()).bold())
+    )?;
+    draw_art(out, flag.colorize(logo), 0, 2, rows)?;
+    out.flush()?;
+    loop {
+        match read_key()? {
+            Key::Enter | Key::Char('y' | 'Y') => return Ok(true),
+            Key::Quit | Key::Char('n' | 'N') => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// Color adjustments chosen so far, applied to every preview
+#[derive(Clone, Copy)]
+struct Look {
+    light_dark: LightDark,
+    lightness: Option<f32>,
+    color_mode: ColorMode,
+}
+
+fn flag_colors(colors: &Arc<[Color]>, orientation: Orientation, look: Look) -> FlagColors {
+    FlagColors {
+        color_scheme: colors.clone(),
+        orientation,
+        light_dark: Some(look.light_dark),
+        lightness: look.lightness,
+        color_mode: look.color_mode,
+    }
+}
+
+/// Draw colored art with its top left corner at `column`, `row`, leaving out lines below the
+/// bottom of the terminal
+fn draw_art(
+    out: &mut Stdout,
+    art: Vec<StyledContent<String>>,
+    column: u16,
+    mut row: u16,
+    rows: u16,
+) -> Result<()> {
+    queue!(out, MoveTo(column, row))?;
+    for content in art {
+        for (idx, part) in content.content().split('\n').enumerate() {
+            if idx > 0 {
+                row += 1;
+                queue!(out, MoveTo(column, row))?;
+            }
+            if row < rows && !part.is_empty() {
+                queue!(
+                    out,
+                    PrintStyledContent(StyledContent::new(*content.style(), part))
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
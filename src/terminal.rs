@@ -0,0 +1,146 @@
+//! Queries about the terminal mirafetch is running in
+
+use std::{
+    env,
+    io::{stdout, IsTerminal},
+    time::Duration,
+};
+
+use crate::config::ColorMode;
+
+/// Whether to print plain text, because the user asked for no colors with `NO_COLOR`
+/// (<https://no-color.org>) or the output goes to a file or pipe
+#[must_use]
+pub fn plain_output() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) || !stdout().is_terminal()
+}
+
+/// Number of columns and rows of the terminal stdout is connected to, or `None` if it isn't a
+/// terminal or its size is unknown, like on a pty whose size was never set
+#[must_use]
+pub fn size() -> Option<(usize, usize)> {
+    if !stdout().is_terminal() {
+        return None;
+    }
+    crossterm::terminal::size()
+        .ok()
+        .filter(|(columns, rows)| *columns > 0 && *rows > 0)
+        .map(|(columns, rows)| (usize::from(columns), usize::from(rows)))
+}
+
+/// Guess which colors the terminal supports from the environment variables most terminals set
+#[must_use]
+pub fn color_mode() -> ColorMode {
+    let var = |name| env::var(name).unwrap_or_default().to_ascii_lowercase();
+    let (colorterm, term, term_program) = (var("COLORTERM"), var("TERM"), var("TERM_PROGRAM"));
+    if colorterm == "truecolor"
+        || colorterm == "24bit"
+        || term.ends_with("-direct")
+        || ["iterm.app", "wezterm", "vscode"].contains(&term_program.as_str())
+        || env::var_os("WT_SESSION").is_some()
+        // The Windows console supports 24-bit color since Windows 10 and doesn't set TERM
+        || (cfg!(windows) && term.is_empty())
+    {
+        ColorMode::Truecolor
+    } else if term.contains("256color") || term_program == "apple_terminal" {
+        ColorMode::Ansi256
+    } else {
+        ColorMode::Ansi16
+    }
+}
+
+/// Ask the terminal for its background color with the OSC 11 escape sequence
+///
+/// Returns `None` if there is no terminal, or it doesn't answer within `timeout`
+#[cfg(unix)]
+#[must_use]
+pub fn background_color(timeout: Duration) -> Option<(u8, u8, u8)> {
+    use crossterm::terminal;
+    use std::fs::OpenOptions;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    terminal::enable_raw_mode().ok()?;
+    // Every terminal answers the device attributes request sent after the background color
+    // request, so terminals without OSC 11 support don't make us wait for the full timeout
+    let response = query(&mut tty, b"\x1b]11;?\x1b\\\x1b[c", timeout);
+    terminal::disable_raw_mode().ok();
+    parse_osc_color(&response?)
+}
+
+#[cfg(not(unix))]
+#[must_use]
+pub const fn background_color(_timeout: Duration) -> Option<(u8, u8, u8)> {
+    None
+}
+
+/// Send `request` and read the response until the answer to a device attributes request arrives
+/// or `timeout` expires
+#[cfg(unix)]
+fn query(tty: &mut std::fs::File, request: &[u8], timeout: Duration) -> Option<String> {
+    use std::{
+        io::{Read, Write},
+        os::fd::AsRawFd,
+        time::Instant,
+    };
+
+    tty.write_all(request).ok()?;
+    let deadline = Instant::now() + timeout;
+    let mut response = Vec::new();
+    while !is_device_attributes_end(&response) {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        let mut pollfd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let remaining = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+        // SAFETY: `pollfd` is a single valid pollfd for the duration of the call
+        if unsafe { libc::poll(&raw mut pollfd, 1, remaining) } <= 0 {
+            break;
+        }
+        let mut buf = [0; 64];
+        match tty.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => response.extend_from_slice(&buf[..read]),
+        }
+    }
+    Some(String::from_utf8_lossy(&response).into_owned())
+}
+
+/// Whether `response` ends with an answer to a device attributes request, `ESC [ ? ... c`
+fn is_device_attributes_end(response: &[u8]) -> bool {
+    response.ends_with(b"c")
+        && response
+            .windows(3)
+            .rposition(|x| x == b"\x1b[?")
+            .is_some_and(|start| {
+                response[start + 3..response.len() - 1]
+                    .iter()
+                    .all(|x| x.is_ascii_digit() || *x == b';')
+            })
+}
+
+/// Parse the color in an OSC 10/11 response like `ESC ] 11 ; rgb:ffff/ffff/ffff ESC \`, where
+/// each channel has 1 to 4 hex digits
+pub(crate) fn parse_osc_color(response: &str) -> Option<(u8, u8, u8)> {
+    let start = response.find("rgb:")? + "rgb:".len();
+    let end = response[start..]
+        .find(['\x1b', '\x07'])
+        .map_or(response.len(), |end| start + end);
+    let mut channels = response[start..end].split('/').map(|channel| {
+        if !(1..=4).contains(&channel.len()) {
+            return None;
+        }
+        let max = 16u32.checked_pow(u32::try_from(channel.len()).ok()?)? - 1;
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    });
+    let color = (channels.next()??, channels.next()??, channels.next()??);
+    channels.next().is_none().then_some(color)
+}
@@ -1 +1,616 @@
+use std::time::{Duration, Instant};
 
+use arcstr::ArcStr;
+use crossterm::style::{Color, Stylize};
+use itertools::Itertools;
+
+use crate::{
+    color::{quantize, set_lightness, Bound, Oklab},
+    colorizer::{Colorizer, DefaultColors, FlagColors},
+    config::{ColorMode, Layout, LightDark, Orientation},
+    custom::CustomModule,
+    info::{icon_ids, Collector, Entry, Field, InOrder, Probe},
+    layout::{self, Line},
+    lint::lint_icons,
+    terminal::parse_osc_color,
+    util::{
+        fill_template, format_color, get_colorscheme, get_icon, get_small_variant, parse_color,
+        AsciiArt, AsciiArtUnprocessed, NotFound, Variant,
+    },
+};
+
+fn probe(field: Field, value: &str) -> Probe {
+    Probe {
+        field,
+        entry: Some(Entry::Os(ArcStr::from(value))),
+        elapsed: Some(Duration::ZERO),
+    }
+}
+
+#[test]
+fn in_order_follows_module_list() {
+    let finished = [
+        probe(Field::Cpu, "cpu"),
+        probe(Field::Os, "os"),
+        probe(Field::Kernel, "kernel"),
+    ];
+    let modules = [Field::Os, Field::Kernel, Field::Os, Field::Cpu];
+    let fields = InOrder::new(finished.into_iter(), &modules)
+        .map(|probe| probe.field)
+        .collect_vec();
+    assert_eq!(fields, [Field::Os, Field::Kernel, Field::Cpu]);
+}
+
+#[test]
+fn in_order_stops_at_missing_module() {
+    let finished = [probe(Field::Kernel, "kernel")];
+    let modules = [Field::Os, Field::Kernel];
+    assert_eq!(InOrder::new(finished.into_iter(), &modules).count(), 0);
+}
+
+#[test]
+fn collector_times_out_missing_modules() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    tx.send(probe(Field::Kernel, "kernel")).unwrap();
+    tx.send(probe(Field::Kernel, "duplicate")).unwrap();
+    let modules = [Field::Os, Field::Kernel];
+    let timeout = Some(Duration::from_millis(20));
+    let probes = Collector::new(rx, &modules, timeout).collect_vec();
+    assert_eq!(probes.len(), 2);
+    assert_eq!(probes[0], probe(Field::Kernel, "kernel"));
+    assert_eq!(probes[1].field, Field::Os);
+    assert!(probes[1].timed_out());
+    assert_eq!(
+        probes[1].lines(None, None),
+        [(ArcStr::from("OS"), arcstr::literal!("unavailable"))]
+    );
+}
+
+#[test]
+fn collector_ends_when_all_modules_reported() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    tx.send(probe(Field::Os, "os")).unwrap();
+    let probes = Collector::new(rx, &[Field::Os], None).collect_vec();
+    assert_eq!(probes, [probe(Field::Os, "os")]);
+    drop(tx);
+}
+
+#[test]
+fn fill_template_replaces_placeholders() {
+    let values = [("value", "8 GiB".to_owned()), ("index", "2".to_owned())];
+    assert_eq!(
+        fill_template("GPU {index}: {value}", &values),
+        "GPU 2: 8 GiB"
+    );
+    assert_eq!(fill_template("{unknown}{value}", &values), "8 GiB");
+    assert_eq!(fill_template("{ value } {}", &values), "{ value } {}");
+}
+
+#[test]
+fn entry_lines_use_label_and_value_templates() {
+    let gpus = Entry::Gpus(vec![ArcStr::from("Intel"), ArcStr::from("AMD")]);
+    assert_eq!(
+        gpus.lines(Some("GPU {index}"), Some("[{value}]")),
+        [
+            (ArcStr::from("GPU 1"), ArcStr::from("[Intel]")),
+            (ArcStr::from("GPU 2"), ArcStr::from("[AMD]")),
+        ]
+    );
+}
+
+#[test]
+fn parse_color_reads_hex_and_names() {
+    assert_eq!(
+        parse_color("#ff8000").unwrap(),
+        Color::Rgb {
+            r: 0xff,
+            g: 0x80,
+            b: 0x00
+        }
+    );
+    assert_eq!(parse_color("dark_grey").unwrap(), Color::DarkGrey);
+    assert_eq!(parse_color("12").unwrap(), Color::AnsiValue(12));
+    assert!(parse_color("#ff80").is_err());
+    assert!(parse_color("#gg8000").is_err());
+    assert!(parse_color("no_such_color").is_err());
+}
+
+#[test]
+fn format_color_round_trips() {
+    for color in [
+        Color::Reset,
+        Color::DarkGrey,
+        Color::Rgb { r: 1, g: 2, b: 3 },
+        Color::AnsiValue(200),
+    ] {
+        assert_eq!(parse_color(&format_color(color)).unwrap(), color);
+    }
+}
+
+fn command_module(command: &str) -> CustomModule {
+    CustomModule {
+        label: "Test".to_owned(),
+        command: Some(command.to_owned()),
+        file: None,
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn custom_module_shows_first_line() {
+    let module = command_module("echo; echo '  first '; echo second");
+    assert_eq!(module.run(None), Some(ArcStr::from("first")));
+    assert_eq!(command_module("echo failed; exit 1").run(None), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn custom_command_does_not_wait_for_background_programs() {
+    let start = Instant::now();
+    let module = command_module("sleep 5 & echo done");
+    assert_eq!(module.run(None), Some(ArcStr::from("done")));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn custom_command_is_killed_at_deadline() {
+    let start = Instant::now();
+    let module = command_module("sleep 5; echo late");
+    let deadline = start + Duration::from_millis(50);
+    assert_eq!(module.run(Some(deadline)), None);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn not_found_suggests_closest_names() {
+    let candidates = ["ubuntu", "kubuntu", "lubuntu", "xubuntu", "ubuntu", "arch"];
+    let err = NotFound::new("icon", "ubunto", candidates);
+    assert_eq!(err.suggestions, ["ubuntu", "kubuntu", "lubuntu"]);
+    assert_eq!(
+        err.to_string(),
+        "Could not find icon \"ubunto\", did you mean \"ubuntu\", \"kubuntu\" or \"lubuntu\"?"
+    );
+    let err = NotFound::new("icon", "gentoo", ["arch"]);
+    assert!(err.suggestions.is_empty());
+    assert_eq!(err.to_string(), "Could not find icon \"gentoo\"");
+}
+
+#[test]
+fn unknown_scheme_is_not_found() {
+    let err = get_colorscheme(&"lesbain", &rustc_hash::FxHashMap::default()).unwrap_err();
+    let err = err.downcast::<NotFound>().unwrap();
+    assert_eq!(err.kind, "scheme");
+    assert_eq!(err.suggestions.first().map(String::as_str), Some("lesbian"));
+}
+
+#[test]
+fn unknown_icon_is_not_found() {
+    let err = get_icon(&"ubunto", &[]).unwrap_err();
+    let err = err.downcast::<NotFound>().unwrap();
+    assert_eq!(err.kind, "icon");
+    assert_eq!(err.suggestions.first().map(String::as_str), Some("ubuntu"));
+}
+
+#[test]
+fn parse_osc_color_scales_channels() {
+    assert_eq!(
+        parse_osc_color("\x1b]11;rgb:ffff/8080/0000\x1b\\"),
+        Some((255, 128, 0))
+    );
+    assert_eq!(
+        parse_osc_color("\x1b]11;rgb:f/8/0\x07"),
+        Some((255, 136, 0))
+    );
+    assert_eq!(
+        parse_osc_color("\x1b]11;rgb:1e1e/1e1e/1e1e"),
+        Some((30, 30, 30))
+    );
+}
+
+#[test]
+fn parse_osc_color_rejects_malformed_responses() {
+    assert_eq!(parse_osc_color("\x1b]11;rgb:ffff/ffff\x1b\\"), None);
+    assert_eq!(parse_osc_color("\x1b]11;rgb:ffff/ffff/ffff/ffff\x07"), None);
+    assert_eq!(parse_osc_color("\x1b]11;rgb:fffff/0/0\x07"), None);
+    assert_eq!(parse_osc_color("\x1b]11;rgb:gg/0/0\x07"), None);
+    assert_eq!(parse_osc_color("\x1b[?62;22c"), None);
+}
+
+fn lightness(color: Color) -> f32 {
+    let Color::Rgb { r, g, b } = color else {
+        panic!("{color:?} is not an RGB color");
+    };
+    Oklab::from_rgb(r, g, b).l
+}
+
+#[test]
+fn set_lightness_lowers_oklab_lightness() {
+    let pink = Color::Rgb {
+        r: 0xf5,
+        g: 0xa9,
+        b: 0xb8,
+    };
+    let dimmed = set_lightness(pink, 0.3, Bound::Exact);
+    assert!((lightness(dimmed) - 0.3).abs() < 0.01, "{dimmed:?}");
+    assert_eq!(set_lightness(pink, 0.3, Bound::AtLeast), pink);
+    assert_eq!(set_lightness(pink, 0.95, Bound::AtMost), pink);
+    assert_eq!(set_lightness(Color::Red, 0.3, Bound::Exact), Color::Red);
+}
+
+#[test]
+fn flag_lightness_applies_on_dark_backgrounds() {
+    let flag = |lightness| FlagColors {
+        color_scheme: [Color::Rgb {
+            r: 0xf5,
+            g: 0xa9,
+            b: 0xb8,
+        }]
+        .into(),
+        orientation: Orientation::Horizontal,
+        light_dark: Some(LightDark::Dark),
+        lightness,
+        color_mode: ColorMode::Truecolor,
+    };
+    let dimmed = flag(Some(0.3)).colors()[0];
+    assert!((lightness(dimmed) - 0.3).abs() < 0.01, "{dimmed:?}");
+    let unchanged = flag(None).colors()[0];
+    assert!(lightness(unchanged) > LightDark::Dark.default_lightness());
+}
+
+#[test]
+fn vertical_flag_colors_non_ascii_icons_by_column() {
+    let manjaro = get_icon(&"manjaro", &[]).unwrap();
+    let flag = FlagColors {
+        color_scheme: [Color::Red, Color::Blue].into(),
+        orientation: Orientation::Vertical,
+        light_dark: None,
+        lightness: None,
+        color_mode: ColorMode::Truecolor,
+    };
+    let pieces = flag.colorize(&manjaro);
+    let first_line = pieces
+        .iter()
+        .take_while(|piece| piece.content() != "\n")
+        .collect_vec();
+    assert_eq!(first_line.len(), usize::from(manjaro.width));
+    let half = first_line.len() / 2;
+    assert!(first_line[..half]
+        .iter()
+        .all(|piece| piece.style().foreground_color == Some(Color::Red)));
+    assert!(first_line[half..]
+        .iter()
+        .all(|piece| piece.style().foreground_color == Some(Color::Blue)));
+}
+
+#[test]
+fn quantize_picks_closest_palette_color() {
+    let orange = Color::Rgb {
+        r: 0xff,
+        g: 0x87,
+        b: 0x00,
+    };
+    assert_eq!(quantize(orange, ColorMode::Truecolor), orange);
+    assert_eq!(quantize(orange, ColorMode::Ansi256), Color::AnsiValue(208));
+    let grey = Color::Rgb {
+        r: 0x80,
+        g: 0x80,
+        b: 0x80,
+    };
+    assert_eq!(quantize(grey, ColorMode::Ansi256), Color::AnsiValue(244));
+    assert_eq!(
+        quantize(Color::Rgb { r: 250, g: 5, b: 5 }, ColorMode::Ansi16),
+        Color::Red
+    );
+    assert_eq!(
+        quantize(Color::AnsiValue(196), ColorMode::Ansi16),
+        Color::Red
+    );
+    assert_eq!(
+        quantize(Color::AnsiValue(3), ColorMode::Ansi16),
+        Color::AnsiValue(3)
+    );
+    assert_eq!(quantize(Color::Blue, ColorMode::Ansi16), Color::Blue);
+}
+
+fn lines(text: &[&str]) -> Vec<Line> {
+    text.iter()
+        .map(|line| vec![(*line).to_owned().stylize()])
+        .collect()
+}
+
+fn text(lines: impl IntoIterator<Item = Line>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| line.iter().map(|piece| piece.content().as_str()).collect())
+        .collect()
+}
+
+#[test]
+fn truncate_adds_ellipsis() {
+    let line = vec!["abc".to_owned().red(), "def".to_owned().blue()];
+    assert_eq!(text([layout::truncate(line.clone(), 6)]), ["abcdef"]);
+    assert_eq!(text([layout::truncate(line.clone(), 5)]), ["abcd…"]);
+    assert_eq!(text([layout::truncate(line, 0)]), [""]);
+}
+
+#[test]
+fn width_counts_terminal_columns() {
+    assert_eq!(layout::text_width("arch"), 4);
+    assert_eq!(layout::text_width("日本"), 4);
+    assert_eq!(layout::text_width("e\u{301}"), 1);
+    // A wide character that would only half fit is left out
+    let line = vec!["ab日本".to_owned().red()];
+    assert_eq!(layout::width(&line), 6);
+    assert_eq!(text([layout::truncate(line, 4)]), ["ab…"]);
+}
+
+#[test]
+fn split_lines_breaks_pieces_on_newlines() {
+    let pieces = ["ab\nc".to_owned().red(), "d\n".to_owned().blue()];
+    assert_eq!(text(layout::split_lines(pieces)), ["ab", "cd"]);
+}
+
+#[test]
+fn arrange_centers_shorter_column() {
+    let logo = lines(&["####", "####", "####", "####"]);
+    let info = lines(&["one", "two"]);
+    assert_eq!(
+        text(layout::arrange(
+            Layout::LogoLeft,
+            logo.clone(),
+            info.clone(),
+            None
+        )),
+        ["####", "####   one", "####   two", "####"]
+    );
+    assert_eq!(
+        text(layout::arrange(Layout::LogoRight, logo, info, None)),
+        ["      ####", "one   ####", "two   ####", "      ####"]
+    );
+}
+
+#[test]
+fn arrange_ignores_unknown_terminal_width() {
+    let logo = lines(&["####"]);
+    let info = lines(&["a long line of information"]);
+    let expected = ["####   a long line of information"];
+    assert_eq!(
+        text(layout::arrange(
+            Layout::LogoLeft,
+            logo.clone(),
+            info.clone(),
+            Some(0)
+        )),
+        expected
+    );
+    let streamed = layout::arrange_streaming(Layout::LogoLeft, logo, info.into_iter(), Some(0));
+    assert_eq!(text(streamed), expected);
+}
+
+#[test]
+fn arrange_falls_back_to_logo_on_top_when_narrow() {
+    let logo = lines(&["####"]);
+    let info = lines(&["a long line of information"]);
+    assert_eq!(
+        text(layout::arrange(Layout::LogoLeft, logo, info, Some(20))),
+        ["####", "", "a long line of info…"]
+    );
+}
+
+#[test]
+fn arrange_streaming_aligns_columns_at_top() {
+    let logo = lines(&["####", "####", "####"]);
+    let info = lines(&["one"]);
+    assert_eq!(
+        text(layout::arrange_streaming(
+            Layout::LogoLeft,
+            logo.clone(),
+            info.into_iter(),
+            None
+        )),
+        ["####   one", "####", "####"]
+    );
+    let info = lines(&["one", "two", "three", "four"]);
+    assert_eq!(
+        text(layout::arrange_streaming(
+            Layout::LogoLeft,
+            logo,
+            info.into_iter(),
+            None
+        )),
+        ["####   one", "####   two", "####   three", "       four"]
+    );
+}
+
+#[test]
+fn bundled_icons_pass_lint() {
+    let issues = lint_icons(include_str!("../data/icons.yaml")).unwrap();
+    assert!(issues.is_empty(), "{}", issues.iter().join("\n"));
+}
+
+#[test]
+fn lint_measures_wide_characters() {
+    let icons = concat!(
+        "- name: ['wide']\n",
+        "  width: 4\n",
+        "  colors:\n",
+        "    - !AnsiValue 1\n",
+        "  art: |-\n",
+        "    ${c1}日本\n",
+        "    ab\u{301}cd\n",
+    );
+    assert_eq!(lint_icons(icons).unwrap(), []);
+}
+
+#[test]
+fn icons_with_undefined_colors_are_rejected() {
+    let icon = |art| {
+        let yaml = format!("name: ['test']\nwidth: 2\ncolors: [Red]\nart: '{art}'\n");
+        AsciiArt::try_from(serde_yaml::from_str::<AsciiArtUnprocessed>(&yaml).unwrap())
+    };
+    assert!(icon("${c1}ab").is_ok());
+    let err = icon("${c1}a${c2}b").unwrap_err();
+    assert_eq!(err.to_string(), "${c2} has no color, only 1 defined");
+    assert!(icon("${c0}ab").is_err());
+}
+
+#[test]
+fn lint_finds_broken_icons() {
+    let icons = concat!(
+        "- name: ['one', 'Shared']\n",
+        "  width: 4\n",
+        "  colors:\n",
+        "    - !AnsiValue 1\n",
+        "  art: |-\n",
+        "    ${c1}abc${c2}\n",
+        "    ab     \n",
+        "- name: ['shared']\n",
+        "  width: 2\n",
+        "  colors: []\n",
+        "  art: |-\n",
+        "    ab\n",
+    );
+    let issues = lint_icons(icons).unwrap();
+    let messages = issues.iter().map(ToString::to_string).collect_vec();
+    assert_eq!(
+        messages,
+        [
+            "one: ${c2} has no color, only 1 defined",
+            "one: width is 4 but the widest line is 3 columns wide",
+            "one: trailing whitespace past the width of 4 on lines 2",
+            "shared: name \"shared\" is already used by icon #1",
+        ]
+    );
+}
+
+fn icon(names: &[&str]) -> AsciiArt {
+    AsciiArt {
+        name: names.iter().map(|name| (*name).to_owned()).collect(),
+        colors: vec![Color::Reset],
+        width: 1,
+        height: 1,
+        art: vec![(1, "#".to_owned())],
+        variant: Variant::Full,
+    }
+}
+
+#[test]
+fn get_icon_finds_exact_names() {
+    let kinoite = get_icon(&"KINOITE", &[]).unwrap();
+    assert_eq!(kinoite.name, ["fedora kinoite", "kinoite"]);
+    assert_eq!(kinoite.width, 33);
+    // Icons in `extra` take precedence over built-in icons with the same name
+    let extra = [icon(&["laxeros"]), icon(&["kinoite"])];
+    let kinoite = get_icon(&"Kinoite", &extra).unwrap();
+    assert_eq!(kinoite.name, ["kinoite"]);
+    assert_eq!(kinoite.width, 1);
+}
+
+#[test]
+fn get_icon_matches_prefixes() {
+    // `archcraft` has its own icon, which takes precedence over `arch*`
+    let archcraft = get_icon(&"archcraft", &[]).unwrap();
+    assert_eq!(archcraft.name[0], "archcraft");
+    let arch = get_icon(&"Arch Linux ARM", &[]).unwrap();
+    assert_eq!(arch.name[0], "arch");
+    // The longest prefix wins
+    let archstrike = get_icon(&"archstrike-rolling", &[]).unwrap();
+    assert_eq!(archstrike.name[0], "archstrike");
+}
+
+#[test]
+fn get_icon_prefers_extra_within_each_kind_of_match() {
+    let extra = [icon(&["arch*"])];
+    // A built-in exact name beats a prefix in `extra`
+    assert_eq!(get_icon(&"archcraft", &extra).unwrap().name[0], "archcraft");
+    // A prefix in `extra` beats a built-in prefix, even a longer one
+    assert_eq!(get_icon(&"archstrike-rolling", &extra).unwrap().width, 1);
+}
+
+#[test]
+fn icon_ids_go_from_specific_to_generic() {
+    let ids = icon_ids(
+        arcstr::literal!("pika"),
+        vec![arcstr::literal!("ubuntu"), arcstr::literal!("debian")],
+        Some(arcstr::literal!("Pika OS")),
+    );
+    let mut expected = vec!["pika", "ubuntu", "debian", "Pika OS"];
+    if cfg!(target_os = "linux") {
+        expected.push("linux");
+    }
+    assert_eq!(ids, expected);
+}
+
+#[test]
+fn variants_are_named_by_suffix() {
+    let variant = |name| get_icon(&name, &[]).unwrap().variant;
+    assert_eq!(variant("arch"), Variant::Full);
+    assert_eq!(variant("arch_small"), Variant::Small);
+    assert_eq!(variant("arch_old"), Variant::Old);
+    assert_eq!(variant("ubuntu_old02"), Variant::Old);
+}
+
+#[test]
+fn small_variant_is_found_by_name() {
+    let arch = get_icon(&"arch", &[]).unwrap();
+    let small = get_small_variant(&arch, &[]).unwrap();
+    assert_eq!(small.name[0], "arch_small");
+    assert!(small.width < arch.width);
+    // Small icons are their own small variant
+    assert_eq!(
+        get_small_variant(&small, &[]).unwrap().name[0],
+        "arch_small"
+    );
+    // Icons named like a small variant are skipped unless they are small
+    let mut not_small = icon(&["laxeros_small"]);
+    not_small.variant = Variant::Full;
+    let laxeros = get_icon(&"laxeros", &[]).unwrap();
+    assert!(get_small_variant(&laxeros, &[not_small]).is_none());
+}
+
+#[test]
+fn fits_leaves_room_for_information() {
+    let size = (60, 20);
+    assert!(layout::fits(Layout::LogoLeft, 20, 20, size));
+    assert!(!layout::fits(Layout::LogoLeft, 50, 10, size));
+    assert!(!layout::fits(Layout::LogoRight, 20, 21, size));
+    assert!(layout::fits(Layout::LogoTop, 60, 20, size));
+    assert!(layout::fits(Layout::NoLogo, 100, 100, size));
+}
+
+#[test]
+fn default_colors_are_quantized() {
+    let mut logo = icon(&["test"]);
+    logo.colors = vec![
+        Color::AnsiValue(196),
+        Color::Rgb {
+            r: 0,
+            g: 0,
+            b: 0xff,
+        },
+    ];
+    logo.art = vec![(1, "#".to_owned()), (2, "#".to_owned())];
+    let colors = |color_mode| {
+        DefaultColors { color_mode }
+            .colorize(&logo)
+            .iter()
+            .map(|piece| piece.style().foreground_color)
+            .collect_vec()
+    };
+    assert_eq!(
+        colors(ColorMode::Ansi16),
+        [Some(Color::Red), Some(Color::DarkBlue)]
+    );
+    assert_eq!(
+        colors(ColorMode::Truecolor),
+        [
+            Some(Color::AnsiValue(196)),
+            Some(Color::Rgb {
+                r: 0,
+                g: 0,
+                b: 0xff
+            })
+        ]
+    );
+}
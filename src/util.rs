@@ -1,98 +1,454 @@
 use anyhow::anyhow;
 use crossterm::style::Color;
-use lazy_static::lazy_static;
+use itertools::Itertools;
 use num::Unsigned;
-use regex::Regex;
+use regex::{Captures, Regex};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeAs};
-use std::{iter::zip, num::ParseIntError, str::FromStr, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    hash::BuildHasher,
+    iter::zip,
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, LazyLock},
+};
 
-// TODO: see if you can have these as structs set at compile time
-const ICONS: &str = include_str!("../data/icons.yaml");
-const FLAGS: &str = include_str!("../data/flags.toml");
-/// .
+/// Tables of the built-in icons and flags, generated from `data/` by `build.rs`
+#[allow(clippy::unreadable_literal)]
+mod built_in {
+    use super::{Color, StaticIcon};
+
+    include!(concat!(env!("OUT_DIR"), "/icons.rs"));
+    include!(concat!(env!("OUT_DIR"), "/flags.rs"));
+}
+use built_in::{FLAGS, ICONS, ICON_NAMES, ICON_PREFIXES};
+
+/// A built-in icon, as stored in the tables generated by `build.rs`
+struct StaticIcon {
+    names: &'static [&'static str],
+    colors: &'static [Color],
+    width: u16,
+    height: u16,
+    art: &'static [(u8, &'static str)],
+    variant: Option<Variant>,
+}
+
+impl From<&StaticIcon> for AsciiArt {
+    fn from(icon: &StaticIcon) -> Self {
+        let name = icon.names.iter().map(|&name| name.to_owned()).collect_vec();
+        let variant = icon.variant.unwrap_or_else(|| Variant::from_names(&name));
+        Self {
+            name,
+            colors: icon.colors.to_vec(),
+            width: icon.width,
+            height: icon.height,
+            art: icon
+                .art
+                .iter()
+                .map(|&(idx, text)| (idx, text.to_owned()))
+                .collect(),
+            variant,
+        }
+    }
+}
+
+/// Find a built-in icon by its exact lowercase name
+fn built_in_icon(name: &str) -> Option<AsciiArt> {
+    ICON_NAMES.get(name).map(|&idx| AsciiArt::from(&ICONS[idx]))
+}
+
+/// Find the built-in icon with the longest name ending in `*` that `name` starts with, see
+/// [`get_icon`]
+fn built_in_prefixed_icon(name: &str) -> Option<AsciiArt> {
+    ICON_PREFIXES
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|&(_, idx)| AsciiArt::from(&ICONS[idx]))
+}
+
+/// Find an icon by name, searching the icons in `extra` before the built-in icons
+///
+/// Icon names ending in `*` match every name starting with the rest of it, eg `arch*` matches
+/// `archcraft`. Icons are chosen in this order, taking the first icon in file order on ties:
+///
+/// 1. An icon in `extra` with the exact name
+/// 2. A built-in icon with the exact name
+/// 3. The icon in `extra` with the longest matching prefix
+/// 4. The built-in icon with the longest matching prefix
 ///
 /// # Errors
 ///
-/// This function will return an error if the icon cannot be found
+/// This function will return a [`NotFound`] error if the icon cannot be found
 #[allow(dead_code)]
-pub fn get_icon(icon_name: &impl ToString) -> anyhow::Result<AsciiArt> {
+pub fn get_icon(icon_name: &impl ToString, extra: &[AsciiArt]) -> anyhow::Result<AsciiArt> {
     let icon_name = &icon_name.to_string().to_ascii_lowercase();
-    let icons = serde_yaml::from_str::<Vec<AsciiArtUnprocessed>>(ICONS)
-        .expect("Could not parse icons file")
+    if let Some(icon) = extra.iter().find(|item| item.name.contains(icon_name)) {
+        return Ok(icon.clone());
+    }
+    if let Some(icon) = built_in_icon(icon_name) {
+        return Ok(icon);
+    }
+    let prefixed = extra
+        .iter()
+        .filter_map(|item| {
+            let len = item
+                .name
+                .iter()
+                .filter_map(|x| prefix_len(x, icon_name))
+                .max()?;
+            Some((len, item))
+        })
+        .min_by_key(|(len, _)| Reverse(*len));
+    if let Some((_, icon)) = prefixed {
+        return Ok(icon.clone());
+    }
+    if let Some(icon) = built_in_prefixed_icon(icon_name) {
+        return Ok(icon);
+    }
+    let names = extra
+        .iter()
+        .flat_map(|item| item.name.iter().map(|x| x.trim_end_matches('*')))
+        .chain(ICON_NAMES.keys().copied())
+        .chain(ICON_PREFIXES.iter().map(|(prefix, _)| *prefix));
+    Err(NotFound::new("icon", icon_name, names).into())
+}
+
+/// Find the small variant of `icon`, named like one of its names with `_small` appended
+#[must_use]
+pub fn get_small_variant(icon: &AsciiArt, extra: &[AsciiArt]) -> Option<AsciiArt> {
+    if icon.variant == Variant::Small {
+        return Some(icon.clone());
+    }
+    icon.name.iter().find_map(|name| {
+        get_icon(&format!("{}_small", name.trim_end_matches('*')), extra)
+            .ok()
+            .filter(|icon| icon.variant == Variant::Small)
+    })
+}
+
+/// List every icon sorted by its first name, with the icons in `extra` listed before built-in
+/// icons with the same name
+#[must_use]
+pub fn get_icons(extra: &[AsciiArt]) -> Vec<AsciiArt> {
+    extra
+        .iter()
+        .cloned()
+        .chain(ICONS.iter().map(AsciiArt::from))
+        .sorted_by(|a, b| a.name.first().cmp(&b.name.first()))
+        .collect()
+}
+
+/// Length of `alias` without its trailing `*` if it is a prefix of `name`, or `None` if `alias`
+/// doesn't end in `*` or doesn't match
+fn prefix_len(alias: &str, name: &str) -> Option<usize> {
+    alias
+        .strip_suffix('*')
+        .filter(|prefix| name.starts_with(prefix))
+        .map(str::len)
+}
+
+/// Names listed in the `name` field of an icon, if it is a list of strings
+pub(crate) fn entry_names(entry: &serde_yaml::Value) -> impl Iterator<Item = &str> {
+    entry
+        .get("name")
+        .and_then(serde_yaml::Value::as_sequence)
         .into_iter()
-        .map(|x| TryInto::<AsciiArt>::try_into(x).expect("Could not parse icon"));
-    icons
+        .flatten()
+        .filter_map(serde_yaml::Value::as_str)
+}
+
+/// Load an icon file, containing either a single icon or a list of icons in the same format as
+/// `data/icons.yaml`
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or contains invalid icons
+pub fn load_icon_file(path: &Path) -> anyhow::Result<Vec<AsciiArt>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Could not read icon file {}: {err}", path.display()))?;
+    serde_yaml::from_str::<Vec<AsciiArtUnprocessed>>(&contents)
+        .or_else(|_| serde_yaml::from_str::<AsciiArtUnprocessed>(&contents).map(|x| vec![x]))
+        .map_err(|err| anyhow!("Invalid icon file {}: {err}", path.display()))?
         .into_iter()
-        .find(|item| item.name.contains(&icon_name.to_string()))
-        .map(std::convert::Into::into)
-        .ok_or_else(|| anyhow!(format!("Could not find an icon for {icon_name}")))
+        .map(AsciiArt::try_from)
+        .collect()
 }
 
-/// TODO
+/// Load every `.yaml` icon file in `dir` in alphabetical order, see [`load_icon_file`]
 ///
 /// # Errors
 ///
-/// This function will return an error if the colorscheme cannot be found
+/// This function will return an error if the directory or any of its icon files cannot be read
+pub fn load_icon_dir(dir: &Path) -> anyhow::Result<Vec<AsciiArt>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .filter_ok(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| load_icon_file(path))
+        .flatten_ok()
+        .collect()
+}
+
+/// Find a flag color scheme by name, searching the schemes in `extra` before the built-in schemes
+///
+/// # Errors
+///
+/// This function will return a [`NotFound`] error if the colorscheme cannot be found
 #[allow(dead_code)]
-pub fn get_colorscheme(scheme_name: &impl ToString) -> Arc<[Color]> {
+pub fn get_colorscheme<S: BuildHasher>(
+    scheme_name: &impl ToString,
+    extra: &HashMap<String, Arc<[Color]>, S>,
+) -> anyhow::Result<Arc<[Color]>> {
     let scheme = scheme_name.to_string();
-    let schemes: FxHashMap<String, Vec<(u8, u8, u8)>> =
-        toml::from_str(FLAGS).expect("Failed to parse flags.toml");
+    if let Some(colors) = extra.get(&scheme) {
+        return Ok(colors.clone());
+    }
+    let Some(colors) = FLAGS.get(scheme.as_str()) else {
+        let names = extra
+            .keys()
+            .map(String::as_str)
+            .chain(FLAGS.keys().copied());
+        return Err(NotFound::new("scheme", &scheme, names).into());
+    };
+    Ok(Arc::from(*colors))
+}
+
+/// List every flag color scheme sorted by name, with the schemes in `extra` taking precedence over
+/// built-in schemes with the same name
+#[must_use]
+pub fn get_colorschemes<S: BuildHasher>(
+    extra: &HashMap<String, Arc<[Color]>, S>,
+) -> Vec<(String, Arc<[Color]>)> {
+    let mut schemes = FLAGS
+        .entries()
+        .map(|(name, colors)| ((*name).to_owned(), Arc::from(*colors)))
+        .collect::<FxHashMap<String, Arc<[Color]>>>();
+    schemes.extend(
+        extra
+            .iter()
+            .map(|(name, colors)| (name.clone(), colors.clone())),
+    );
+    schemes
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+        .collect()
+}
+
+/// Error for an icon or scheme name that does not exist, along with the most similar known names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotFound {
+    pub kind: &'static str,
+    pub name: String,
+    pub suggestions: Vec<String>,
+}
+
+impl NotFound {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    /// Create an error for `name`, suggesting the closest of `candidates` by edit distance
+    #[must_use]
+    pub fn new<'a>(
+        kind: &'static str,
+        name: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let name = name.to_string();
+        let max_distance = (name.chars().count() / 3).max(2);
+        let suggestions = candidates
+            .into_iter()
+            .unique()
+            .map(|candidate| (strsim::levenshtein(&name, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .sorted()
+            .take(Self::MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate.to_owned())
+            .collect();
+        Self {
+            kind,
+            name,
+            suggestions,
+        }
+    }
+}
+
+impl Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not find {} \"{}\"", self.kind, self.name)?;
+        if let Some((last, rest)) = self.suggestions.split_last() {
+            let rest = rest.iter().map(|x| format!("\"{x}\"")).join(", ");
+            if rest.is_empty() {
+                write!(f, ", did you mean \"{last}\"?")?;
+            } else {
+                write!(f, ", did you mean {rest} or \"{last}\"?")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// A color in a flag color scheme, either an RGB triple or a string accepted by [`parse_color`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SchemeColor {
+    Rgb(u8, u8, u8),
+    Name(String),
+}
+
+impl TryFrom<&SchemeColor> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &SchemeColor) -> anyhow::Result<Self> {
+        match value {
+            SchemeColor::Rgb(r, g, b) => Ok(Self::Rgb {
+                r: *r,
+                g: *g,
+                b: *b,
+            }),
+            SchemeColor::Name(name) => parse_color(name),
+        }
+    }
+}
+
+/// Convert user defined color schemes, as found in `config.toml`, into colors
+///
+/// # Errors
+///
+/// This function will return an error if any scheme contains an invalid color
+pub fn parse_schemes<S: BuildHasher>(
+    schemes: &HashMap<String, Vec<SchemeColor>, S>,
+) -> anyhow::Result<FxHashMap<String, Arc<[Color]>>> {
     schemes
-        .get(&scheme)
-        .unwrap_or_else(|| panic!("Failed to find scheme {}", &scheme))
         .iter()
-        .map(|(r, g, b)| Color::Rgb {
-            r: *r,
-            g: *g,
-            b: *b,
+        .map(|(name, colors)| {
+            let colors = colors
+                .iter()
+                .map(Color::try_from)
+                .collect::<anyhow::Result<Arc<[Color]>>>()
+                .map_err(|err| anyhow!("Invalid scheme {name}: {err}"))?;
+            if colors.is_empty() {
+                return Err(anyhow!("Invalid scheme {name}: no colors"));
+            }
+            Ok((name.clone(), colors))
         })
         .collect()
 }
+
+/// Load a scheme file in the same format as `data/flags.toml`, also accepting colors in any format
+/// supported by [`SchemeColor`]
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or contains invalid schemes
+pub fn load_scheme_file(path: &Path) -> anyhow::Result<FxHashMap<String, Arc<[Color]>>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Could not read scheme file {}: {err}", path.display()))?;
+    let schemes: FxHashMap<String, Vec<SchemeColor>> = toml::from_str(&contents)
+        .map_err(|err| anyhow!("Invalid scheme file {}: {err}", path.display()))?;
+    parse_schemes(&schemes)
+}
 #[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct AsciiArt {
     pub name: Vec<String>,
     pub colors: Vec<Color>,
     pub width: u16,
     pub height: u16,
     pub art: Vec<(u8, String)>,
+    pub variant: Variant,
+}
+
+/// Version of a logo, when an operating system has more than one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Variant {
+    #[default]
+    Full,
+    /// A compact version for small terminals, named like `arch_small`
+    Small,
+    /// A logo the operating system no longer uses, named like `arch_old`
+    Old,
+}
+
+impl Variant {
+    /// Guess the variant of an icon from the suffix of its first name, where old logos may be
+    /// numbered like `ubuntu_old02`
+    fn from_names(names: &[String]) -> Self {
+        let Some(name) = names.first().map(|name| name.to_lowercase()) else {
+            return Self::Full;
+        };
+        if name.ends_with("_small") {
+            Self::Small
+        } else if name
+            .trim_end_matches(|ch: char| ch.is_ascii_digit())
+            .ends_with("_old")
+        {
+            Self::Old
+        } else {
+            Self::Full
+        }
+    }
 }
 
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
-struct AsciiArtUnprocessed {
+pub(crate) struct AsciiArtUnprocessed {
     pub name: Vec<String>,
     #[serde_as(as = "Vec<ColorRemote>")]
     pub colors: Vec<Color>,
     pub width: u16,
     pub art: String,
+    pub variant: Option<Variant>,
 }
-lazy_static! {
-    static ref ascii_regex: Regex = Regex::new(r"\$\{c(\d*)\}").unwrap();
-}
+pub(crate) static ASCII_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{c(\d*)\}").unwrap());
 impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
     fn try_from(val: AsciiArtUnprocessed) -> anyhow::Result<Self> {
         let height = u16::try_from(val.art.lines().count())?;
-        let color_idx: Vec<u8> = ascii_regex
+        let color_idx: Vec<u8> = ASCII_REGEX
             .captures_iter(&val.art)
             .map(|x| -> anyhow::Result<u8> {
-                str::parse(
-                    x.get(1)
-                        .ok_or_else(|| anyhow!("Invalid Ascii Art"))?
-                        .as_str(),
-                )
-                .map_err(|op: ParseIntError| anyhow!(op))
+                let idx = x
+                    .get(1)
+                    .ok_or_else(|| anyhow!("Invalid Ascii Art"))?
+                    .as_str();
+                str::parse(idx)
+                    .map_err(|op: ParseIntError| anyhow!("Invalid color ${{c{idx}}}: {op}"))
             })
-            .map(std::result::Result::unwrap)
-            .collect();
-        let chunks = ascii_regex
+            .collect::<anyhow::Result<_>>()?;
+        if let Some(idx) = color_idx
+            .iter()
+            .find(|idx| **idx == 0 || usize::from(**idx) > val.colors.len())
+        {
+            return Err(anyhow!(
+                "${{c{idx}}} has no color, only {} defined",
+                val.colors.len()
+            ));
+        }
+        let chunks = ASCII_REGEX
             .split(&val.art)
             .map(std::borrow::ToOwned::to_owned)
             .skip(1)
             .collect::<Vec<String>>();
         let ascii_art = (zip(color_idx, chunks)).collect();
+        let variant = val
+            .variant
+            .unwrap_or_else(|| Variant::from_names(&val.name));
         Ok(Self {
             name: val
                 .name
@@ -104,11 +460,75 @@ impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
             width: val.width,
             height,
             art: ascii_art,
+            variant,
         })
     }
 
     type Error = anyhow::Error;
 }
+/// Parse a color from a hex string like `#ff8000`, a name like `red` or `dark_grey`, or an ANSI
+/// color index like `12`
+///
+/// # Errors
+///
+/// This function will return an error if the string is not a valid hex color, color name or index
+pub fn parse_color(color: &str) -> anyhow::Result<Color> {
+    if let Ok(index) = color.parse::<u8>() {
+        return Ok(Color::AnsiValue(index));
+    }
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 {
+            return Err(anyhow!("Invalid hex color {color}"));
+        }
+        let value = u32::from_str_radix(hex, 16).map_err(|err| anyhow!("{err} in {color}"))?;
+        let [_, r, g, b] = value.to_be_bytes();
+        return Ok(Color::Rgb { r, g, b });
+    }
+    Color::try_from(color).map_err(|()| anyhow!("Unknown color {color}"))
+}
+
+/// Inverse of [`parse_color`], ANSI colors are formatted as their index
+#[must_use]
+pub fn format_color(color: Color) -> String {
+    match color {
+        Color::Reset => "reset".to_owned(),
+        Color::Black => "black".to_owned(),
+        Color::DarkGrey => "dark_grey".to_owned(),
+        Color::Red => "red".to_owned(),
+        Color::DarkRed => "dark_red".to_owned(),
+        Color::Green => "green".to_owned(),
+        Color::DarkGreen => "dark_green".to_owned(),
+        Color::Yellow => "yellow".to_owned(),
+        Color::DarkYellow => "dark_yellow".to_owned(),
+        Color::Blue => "blue".to_owned(),
+        Color::DarkBlue => "dark_blue".to_owned(),
+        Color::Magenta => "magenta".to_owned(),
+        Color::DarkMagenta => "dark_magenta".to_owned(),
+        Color::Cyan => "cyan".to_owned(),
+        Color::DarkCyan => "dark_cyan".to_owned(),
+        Color::White => "white".to_owned(),
+        Color::Grey => "grey".to_owned(),
+        Color::Rgb { r, g, b } => format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::AnsiValue(value) => value.to_string(),
+    }
+}
+
+static TEMPLATE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+/// Replace each `{name}` placeholder in `template` with the matching value, or with nothing if
+/// there is no value called `name`
+#[must_use]
+pub fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    TEMPLATE_REGEX
+        .replace_all(template, |caps: &Captures| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &caps[1])
+                .map_or_else(String::new, |(_, value)| value.clone())
+        })
+        .into_owned()
+}
+
 #[allow(dead_code, clippy::cast_precision_loss)]
 #[must_use]
 pub fn bytecount_format<T>(i: T, precision: usize) -> String